@@ -2,39 +2,49 @@ use gloo::{
     events::{EventListener, EventListenerOptions},
     utils::document,
 };
-use web_sys::{console, wasm_bindgen::JsCast};
+use web_sys::{console, wasm_bindgen::JsCast, HtmlTextAreaElement};
 use yew::prelude::*;
 
-use editor::{GuidGenerator, Tool};
-use math::CanvasPoint;
+use editor::{GuidGenerator, Tool, SIMPLIFY_TOLERANCE};
+use math::{boolean::Operation, CanvasPoint, WorldPoint};
 
 use crate::{
-    CameraState,
-    CameraStateAction, components::{InnerCanvas, Toolbar}, use_pointer_down_callback,
-    use_pointer_move_callback,
-    use_pointer_up_callback, use_shapes::{ShapeCatalogAction, ShapeCatalogState},
+    components::{InnerCanvas, TextEditor, Toolbar},
+    use_pointer_down_callback, use_pointer_move_callback, use_pointer_up_callback,
+    use_shapes::{ShapeCatalogAction, ShapeCatalogState},
+    CameraState, CameraStateAction,
 };
 
 pub static GUID_GENERATOR: GuidGenerator = GuidGenerator::new();
 
+#[derive(Properties, PartialEq)]
+pub struct CanvasProps {
+    /// How far, in viewport pixels, a finished freehand stroke may drift when simplified.
+    #[prop_or(SIMPLIFY_TOLERANCE)]
+    pub simplify_tolerance: f32,
+}
+
 #[function_component]
-pub fn Canvas() -> Html {
+pub fn Canvas(props: &CanvasProps) -> Html {
     let current_tool = use_state(|| Tool::Select);
-    let camera = use_reducer(|| CameraState::default());
+    let camera = use_reducer(CameraState::default);
 
     let global_pointer_down = use_state(|| false);
 
     // hand tool
     let initial_drag = use_state(|| CanvasPoint::new(0.0, 0.0));
-    let temp_canvas_position = use_state(|| CanvasPoint::new(0.0, 0.0));
+    let temp_canvas_position = use_state(WorldPoint::default);
 
     // draw tool
-    let shape_catalog = use_reducer(|| ShapeCatalogState::default());
+    let shape_catalog = use_reducer(ShapeCatalogState::default);
     let active_shape = use_state(|| None);
 
     // select tool
     let selection_box = use_state(|| None);
 
+    // text tool
+    let editing_text = use_state(|| None);
+
     let client_position: UseStateHandle<Option<(i32, i32)>> = use_state(|| None);
 
     use_effect({
@@ -47,6 +57,13 @@ pub fn Canvas() -> Html {
                 "keydown",
                 EventListenerOptions::enable_prevent_default(),
                 move |e| {
+                    // typing into the text editor is not a shortcut
+                    if e.target()
+                        .is_some_and(|t| t.has_type::<HtmlTextAreaElement>())
+                    {
+                        return;
+                    }
+
                     e.prevent_default();
 
                     let e = e
@@ -82,15 +99,45 @@ pub fn Canvas() -> Html {
                         "t" => Some(Tool::Text),
                         "r" => Some(Tool::Rect),
                         "c" => Some(Tool::Circle),
+                        "o" => Some(Tool::Ellipse),
                         "f" => Some(Tool::Freehand),
+                        "l" => Some(Tool::Line),
+                        "A" => Some(Tool::Arrow),
                         _ => None,
                     } {
                         current_tool.set(tool);
                     }
 
+                    // only spend an id when there is something to combine
+                    let tolerance = SIMPLIFY_TOLERANCE / (*camera_state).zoom();
+                    if let Some(operation) = match event_key.as_str() {
+                        "u" => Some(Operation::Union),
+                        "i" => Some(Operation::Intersection),
+                        "d" => Some(Operation::Difference),
+                        "x" => Some(Operation::Xor),
+                        _ => None,
+                    }
+                    .filter(|_| shape_catalog.combinable_count(tolerance) >= 2)
+                    {
+                        shape_catalog.dispatch(ShapeCatalogAction::CombineSelected {
+                            id: GUID_GENERATOR.next_guid(),
+                            operation,
+                            tolerance,
+                        });
+                    }
+
+                    let rotation_step = 15f32.to_radians();
+
                     match event_key.as_str() {
+                        "[" => shape_catalog.dispatch(ShapeCatalogAction::RotateSelected {
+                            radians: -rotation_step,
+                        }),
+                        "]" => shape_catalog.dispatch(ShapeCatalogAction::RotateSelected {
+                            radians: rotation_step,
+                        }),
                         "Escape" => shape_catalog.dispatch(ShapeCatalogAction::UnselectAll),
                         "a" => shape_catalog.dispatch(ShapeCatalogAction::SelectAll),
+                        "e" => shape_catalog.dispatch(ShapeCatalogAction::ToggleElbowSelected),
                         "z" => shape_catalog.dispatch(ShapeCatalogAction::DeletePrevious),
                         "Backspace" => shape_catalog.dispatch(ShapeCatalogAction::DeleteSelected),
                         _ => {}
@@ -111,7 +158,13 @@ pub fn Canvas() -> Html {
                 .expect("query failed");
 
             match tool {
-                Tool::Circle | Tool::Freehand | Tool::Rect => {
+                Tool::Circle
+                | Tool::Ellipse
+                | Tool::Freehand
+                | Tool::Rect
+                | Tool::Line
+                | Tool::Arrow
+                | Tool::Primitive(_) => {
                     canvas_div
                         .set_attribute("class", "cursor-crosshair")
                         .expect("failed to set");
@@ -144,6 +197,7 @@ pub fn Canvas() -> Html {
         shape_catalog.clone(),
         active_shape.clone(),
         selection_box.clone(),
+        editing_text.clone(),
     );
 
     let pointer_move_callback = use_pointer_move_callback(
@@ -160,6 +214,7 @@ pub fn Canvas() -> Html {
 
     let pointer_up_callback = use_pointer_up_callback(
         current_tool.clone(),
+        props.simplify_tolerance,
         camera.clone(),
         temp_canvas_position.clone(),
         global_pointer_down.clone(),
@@ -179,7 +234,8 @@ pub fn Canvas() -> Html {
     html! {
         <div id="canvas" class="overflow-hidden top-0 left-0 relative w-screen h-screen" onpointerdown={pointer_down_callback} onpointermove={pointer_move_callback} onpointerup={pointer_up_callback}>
             <Toolbar current_tool={current_tool} client_position={*client_position} />
-            <InnerCanvas camera={camera} shapes={shape_catalog} selection_box={selection_box}/>
+            <InnerCanvas camera={camera.clone()} shapes={shape_catalog.clone()} selection_box={selection_box} editing={*editing_text}/>
+            <TextEditor camera={camera} shapes={shape_catalog} editing={editing_text}/>
         </div>
     }
 }
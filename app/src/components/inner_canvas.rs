@@ -1,5 +1,5 @@
-use gloo::utils::document;
-use math::CanvasPoint;
+use gloo::utils::{document, window};
+use math::{Aabb, CanvasPoint};
 use yew::prelude::*;
 
 use crate::{use_shapes::ShapeCatalogState, CameraState};
@@ -8,7 +8,29 @@ use crate::{use_shapes::ShapeCatalogState, CameraState};
 pub struct InnerCanvasProps {
     pub camera: UseReducerHandle<CameraState>,
     pub shapes: UseReducerHandle<ShapeCatalogState>,
-    pub selection_box: UseStateHandle<Option<(CanvasPoint, CanvasPoint)>>,
+    pub selection_box: UseStateHandle<Option<Aabb>>,
+    /// The text being edited in place, which the editor draws instead.
+    pub editing: Option<u32>,
+}
+
+/// The part of the canvas on screen, in global coordinates relative to the camera's
+/// origin. This inverts the CSS transform on `#group`, which scales about the centre of
+/// the viewport.
+fn visible_area(camera: &CameraState) -> Aabb {
+    let width = window().inner_width().ok().and_then(|w| w.as_f64());
+    let height = window().inner_height().ok().and_then(|h| h.as_f64());
+    let viewport = CanvasPoint::new(
+        width.unwrap_or_default() as f32,
+        height.unwrap_or_default() as f32,
+    );
+
+    let center = viewport / CanvasPoint::splat(2.0);
+    let zoom = CanvasPoint::splat(camera.zoom());
+    let (x, y, _) = camera.coord();
+    let translation = CanvasPoint::new(x, y);
+    let to_global = |s: CanvasPoint| center + (s - center) / zoom - translation;
+
+    Aabb::from_corners(to_global(CanvasPoint::splat(0.0)), to_global(viewport))
 }
 
 #[function_component]
@@ -37,14 +59,14 @@ pub fn InnerCanvas(props: &InnerCanvasProps) -> Html {
             .get_element_by_id("selected")
             .expect("failed to get query");
 
-        if let Some((position, width_height)) = **selection_box_state {
-            let (x, y) = position.coord();
-            let (w, h) = width_height.coord();
+        if let Some(selection_box) = **selection_box_state {
+            let (x, y) = selection_box.position().coord();
+            let (w, h) = selection_box.width_height().coord();
 
             element
                 .set_attribute(
                     "class",
-                    &format!("fill-sky-100 stroke-sky-900 stroke-width-1 opacity-40"),
+                    "fill-sky-100 stroke-sky-900 stroke-width-1 opacity-40",
                 )
                 .expect("failed to set element");
 
@@ -59,6 +81,7 @@ pub fn InnerCanvas(props: &InnerCanvasProps) -> Html {
     });
 
     let camera_state = props.camera.clone();
+    let visible = visible_area(&camera_state);
 
     html! {
         <svg class="fixed w-screen h-screen">
@@ -68,7 +91,7 @@ pub fn InnerCanvas(props: &InnerCanvasProps) -> Html {
                 <circle id="circle" cx="200" cy="200" r="50" />
             </defs>
             <g id="group">
-                {(*props.shapes).html(&*camera_state)}
+                {(*props.shapes).html(&camera_state, visible, props.editing)}
                 <path id="selected" />
             </g>
         </svg>
@@ -1,7 +1,9 @@
 pub use canvas::*;
 pub use inner_canvas::InnerCanvas;
+pub use text_editor::TextEditor;
 pub use toolbar::Toolbar;
 
 mod canvas;
 mod inner_canvas;
+mod text_editor;
 mod toolbar;
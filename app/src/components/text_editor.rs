@@ -0,0 +1,97 @@
+use editor::Text;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+use crate::{
+    use_shapes::{ShapeCatalogAction, ShapeCatalogState},
+    CameraState,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct TextEditorProps {
+    pub camera: UseReducerHandle<CameraState>,
+    pub shapes: UseReducerHandle<ShapeCatalogState>,
+    /// The text being edited, if any.
+    pub editing: UseStateHandle<Option<u32>>,
+}
+
+/// A textarea laid over the text being edited, matching its box, font and alignment at
+/// the current zoom. The canvas hides the text underneath while it is open.
+#[function_component]
+pub fn TextEditor(props: &TextEditorProps) -> Html {
+    let node = use_node_ref();
+
+    use_effect_with(*props.editing, {
+        let node = node.clone();
+        move |_| {
+            if let Some(textarea) = node.cast::<HtmlTextAreaElement>() {
+                textarea.focus().expect("failed to focus");
+            }
+        }
+    });
+
+    let Some(id) = *props.editing else {
+        return html! {};
+    };
+    let Some((position, text)) = props.shapes.text(id, props.camera.origin()) else {
+        return html! {};
+    };
+
+    let oninput = {
+        let shapes = props.shapes.clone();
+        move |e: InputEvent| {
+            let content = e.target_unchecked_into::<HtmlTextAreaElement>().value();
+            shapes.dispatch(ShapeCatalogAction::EditText { id, content });
+        }
+    };
+
+    let onblur = {
+        let shapes = props.shapes.clone();
+        let editing = props.editing.clone();
+        move |_| {
+            shapes.dispatch(ShapeCatalogAction::FinishText { id });
+            editing.set(None);
+        }
+    };
+
+    let onkeydown = move |e: KeyboardEvent| {
+        if e.key() == "Escape" {
+            e.target_unchecked_into::<HtmlTextAreaElement>()
+                .blur()
+                .expect("failed to blur");
+        }
+    };
+
+    // pointer events stay in the textarea, for placing the caret and selecting
+    let stop = |e: PointerEvent| e.stop_propagation();
+
+    let z = props.camera.zoom();
+    let (x, y) = props.camera.convert_global_to_viewport(position).coord();
+    let (w, h) = text.width_height.coord();
+    // room for the caret after the last character
+    let caret = Text::AVERAGE_ADVANCE * text.font_size;
+    let style = format!(
+        "left: {x}px; top: {y}px; width: {}px; height: {}px; font-family: {}; font-size: {}px; line-height: {}; text-align: {};",
+        (w + caret) * z,
+        h * z,
+        text.font_family,
+        text.font_size * z,
+        Text::LINE_HEIGHT,
+        text.align.css(),
+    );
+
+    html! {
+        <textarea
+            ref={node}
+            class="absolute z-[9998] m-0 p-0 border-0 bg-transparent resize-none overflow-hidden whitespace-pre outline-dashed outline-1 outline-blue-800"
+            {style}
+            value={text.content.clone()}
+            {oninput}
+            {onblur}
+            {onkeydown}
+            onpointerdown={stop}
+            onpointermove={stop}
+            onpointerup={stop}
+        />
+    }
+}
@@ -3,6 +3,10 @@ use yew::{function_component, html, Html, Properties, UseStateHandle};
 
 use crate::use_toolbar_callback;
 
+use shape_picker::ShapePicker;
+
+mod shape_picker;
+
 #[derive(Properties, PartialEq)]
 pub struct ToolbarProps {
     pub current_tool: UseStateHandle<Tool>,
@@ -16,7 +20,10 @@ pub fn Toolbar(props: &ToolbarProps) -> Html {
     let handle_draw = use_toolbar_callback(props.current_tool.clone(), Tool::Rect);
     let handle_text = use_toolbar_callback(props.current_tool.clone(), Tool::Text);
     let handle_circle = use_toolbar_callback(props.current_tool.clone(), Tool::Circle);
+    let handle_ellipse = use_toolbar_callback(props.current_tool.clone(), Tool::Ellipse);
     let handle_freehand = use_toolbar_callback(props.current_tool.clone(), Tool::Freehand);
+    let handle_line = use_toolbar_callback(props.current_tool.clone(), Tool::Line);
+    let handle_arrow = use_toolbar_callback(props.current_tool.clone(), Tool::Arrow);
 
     html! {
         <div id="toolbar" class="group absolute z-[9999] bg-white top-0 right-0 w-[8em] h-screen shadow-lg cursor-auto">
@@ -46,7 +53,23 @@ pub fn Toolbar(props: &ToolbarProps) -> Html {
                         <path d="M0.877075 7.49991C0.877075 3.84222 3.84222 0.877075 7.49991 0.877075C11.1576 0.877075 14.1227 3.84222 14.1227 7.49991C14.1227 11.1576 11.1576 14.1227 7.49991 14.1227C3.84222 14.1227 0.877075 11.1576 0.877075 7.49991ZM7.49991 1.82708C4.36689 1.82708 1.82708 4.36689 1.82708 7.49991C1.82708 10.6329 4.36689 13.1727 7.49991 13.1727C10.6329 13.1727 13.1727 10.6329 13.1727 7.49991C13.1727 4.36689 10.6329 1.82708 7.49991 1.82708Z" fill="currentColor" fill-rule="evenodd" clip-rule="evenodd"></path>
                     </svg>
                 </button>
-                <button id="text" onclick={handle_text} class="cursor-hover cursor-not-allowed focus:bg-gray-200 hover:bg-gray-200 h-12 w-12 grid place-items-center">
+                <button id="ellipse" onclick={handle_ellipse} class="cursor-hover focus:bg-gray-200 hover:bg-gray-200 h-12 w-12 grid place-items-center">
+                    <svg width="15" height="15" viewBox="0 0 15 15" fill="none" xmlns="http://www.w3.org/2000/svg">
+                        <ellipse cx="7.5" cy="7.5" rx="6.5" ry="4.5" stroke="currentColor"></ellipse>
+                    </svg>
+                </button>
+                <ShapePicker current_tool={props.current_tool.clone()} />
+                <button id="line" onclick={handle_line} class="cursor-hover focus:bg-gray-200 hover:bg-gray-200 h-12 w-12 grid place-items-center">
+                    <svg width="15" height="15" viewBox="0 0 15 15" fill="none" xmlns="http://www.w3.org/2000/svg">
+                        <path d="M2.5 12.5L12.5 2.5" stroke="currentColor" stroke-linecap="round"></path>
+                    </svg>
+                </button>
+                <button id="arrow" onclick={handle_arrow} class="cursor-hover focus:bg-gray-200 hover:bg-gray-200 h-12 w-12 grid place-items-center">
+                    <svg width="15" height="15" viewBox="0 0 15 15" fill="none" xmlns="http://www.w3.org/2000/svg">
+                        <path d="M3.64645 11.3536C3.45118 11.1583 3.45118 10.8417 3.64645 10.6465L10.2929 4L6 4C5.72386 4 5.5 3.77614 5.5 3.5C5.5 3.22386 5.72386 3 6 3L11.5 3C11.6326 3 11.7598 3.05268 11.8536 3.14645C11.9473 3.24022 12 3.36739 12 3.5L12 9.00001C12 9.27615 11.7761 9.50001 11.5 9.50001C11.2239 9.50001 11 9.27615 11 9.00001V4.70711L4.35355 11.3536C4.15829 11.5488 3.84171 11.5488 3.64645 11.3536Z" fill="currentColor" fill-rule="evenodd" clip-rule="evenodd"></path>
+                    </svg>
+                </button>
+                <button id="text" onclick={handle_text} class="cursor-hover focus:bg-gray-200 hover:bg-gray-200 h-12 w-12 grid place-items-center">
                     <svg width="15" height="15" viewBox="0 0 15 15" fill="none" xmlns="http://www.w3.org/2000/svg">
                         <path d="M3.94993 2.95002L3.94993 4.49998C3.94993 4.74851 3.74845 4.94998 3.49993 4.94998C3.2514 4.94998 3.04993 4.74851 3.04993 4.49998V2.50004C3.04993 2.45246 3.05731 2.40661 3.07099 2.36357C3.12878 2.18175 3.29897 2.05002 3.49993 2.05002H11.4999C11.6553 2.05002 11.7922 2.12872 11.8731 2.24842C11.9216 2.32024 11.9499 2.40682 11.9499 2.50002L11.9499 2.50004V4.49998C11.9499 4.74851 11.7485 4.94998 11.4999 4.94998C11.2514 4.94998 11.0499 4.74851 11.0499 4.49998V2.95002H8.04993V12.05H9.25428C9.50281 12.05 9.70428 12.2515 9.70428 12.5C9.70428 12.7486 9.50281 12.95 9.25428 12.95H5.75428C5.50575 12.95 5.30428 12.7486 5.30428 12.5C5.30428 12.2515 5.50575 12.05 5.75428 12.05H6.94993V2.95002H3.94993Z" fill="currentColor" fill-rule="evenodd" clip-rule="evenodd"></path>
                     </svg>
@@ -0,0 +1,80 @@
+use editor::{Primitive, PrimitiveKind, ShapeGeometry, Tool};
+use math::CanvasPoint;
+use yew::{function_component, html, use_state, Callback, Html, Properties, UseStateHandle};
+
+/// The diagram shapes on offer, in menu order.
+const KINDS: [PrimitiveKind; 6] = [
+    PrimitiveKind::DIAMOND,
+    PrimitiveKind::TRIANGLE,
+    PrimitiveKind::PARALLELOGRAM,
+    PrimitiveKind::HEXAGON,
+    PrimitiveKind::STAR,
+    PrimitiveKind::Cylinder,
+];
+
+/// A 15 × 15 icon drawn from the shape itself.
+fn icon(kind: PrimitiveKind) -> Html {
+    let d = Primitive::new(
+        kind,
+        CanvasPoint::splat(1.5),
+        CanvasPoint::splat(12.0),
+        false,
+    )
+    .to_path();
+
+    html! {
+        <svg width="15" height="15" viewBox="0 0 15 15" fill="none" xmlns="http://www.w3.org/2000/svg">
+            <path {d} stroke="currentColor" stroke-linejoin="round"></path>
+        </svg>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ShapePickerProps {
+    pub current_tool: UseStateHandle<Tool>,
+}
+
+/// A button for the diagram shape picked last, which also opens a menu of the others.
+#[function_component]
+pub fn ShapePicker(props: &ShapePickerProps) -> Html {
+    let open = use_state(|| false);
+    let picked = use_state(|| PrimitiveKind::DIAMOND);
+
+    let onclick = {
+        let current_tool = props.current_tool.clone();
+        let open = open.clone();
+        let picked = picked.clone();
+        move |_| {
+            current_tool.set(Tool::Primitive(*picked));
+            open.set(!*open);
+        }
+    };
+
+    let pick = |kind: PrimitiveKind| {
+        let current_tool = props.current_tool.clone();
+        let open = open.clone();
+        let picked = picked.clone();
+        Callback::from(move |_| {
+            picked.set(kind);
+            current_tool.set(Tool::Primitive(kind));
+            open.set(false);
+        })
+    };
+
+    html! {
+        <>
+            <button id="shapes" title={picked.name()} {onclick} class="cursor-hover focus:bg-gray-200 hover:bg-gray-200 h-12 w-12 grid place-items-center">
+                { icon(*picked) }
+            </button>
+            if *open {
+                <div id="shape-picker" class="grid grid-cols-3 w-[8em] border-y border-gray-200">
+                    { for KINDS.into_iter().map(|kind| html! {
+                        <button title={kind.name()} onclick={pick(kind)} class="cursor-hover hover:bg-gray-200 h-10 grid place-items-center">
+                            { icon(kind) }
+                        </button>
+                    }) }
+                </div>
+            }
+        </>
+    }
+}
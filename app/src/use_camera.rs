@@ -1,31 +1,48 @@
-use math::CanvasPoint;
+use math::{CanvasPoint, Transform, WorldPoint};
 use yew::Reducible;
 
 pub enum CameraStateAction {
     Refresh,
     MoveCamera {
-        temp_canvas_position: CanvasPoint,
+        temp_canvas_position: WorldPoint,
         offset: CanvasPoint,
     },
 }
 
+/// Where the canvas is panned to, kept in double precision so the canvas really is
+/// unbounded.
+///
+/// Everything `f32` that the camera hands out, from pointer positions to the CSS
+/// transform, is relative to [`CameraState::origin`], a chunk corner near the top left of
+/// the viewport. Those values stay small however far the canvas is panned.
 #[derive(Debug, PartialEq, Clone)]
 pub struct CameraState {
-    canvas_position: CanvasPoint,
+    canvas_position: WorldPoint,
     zoom: f32,
 }
 
 impl CameraState {
+    /// The translation and scale of the CSS transform that places the origin's frame.
     pub fn coord(&self) -> (f32, f32, f32) {
-        let (x, y) = self.canvas_position.coord();
+        let (x, y) = self.translation().coord();
 
         (x, y, self.zoom)
     }
 
-    pub fn canvas_position(&self) -> CanvasPoint {
+    pub fn canvas_position(&self) -> WorldPoint {
         self.canvas_position
     }
 
+    /// The world point that the camera's `f32` coordinates are relative to.
+    pub fn origin(&self) -> WorldPoint {
+        (-self.canvas_position).chunk()
+    }
+
+    /// `canvas_position` seen from the origin's frame.
+    fn translation(&self) -> CanvasPoint {
+        self.canvas_position.relative_to(-self.origin())
+    }
+
     #[inline(always)]
     pub fn zoom_invalid(&self) -> bool {
         self.zoom == 0.0 || self.zoom.is_nan() || self.zoom.is_infinite()
@@ -39,27 +56,41 @@ impl CameraState {
         self.zoom
     }
 
-    pub fn convert_viewport_to_global(&self, other: CanvasPoint) -> CanvasPoint {
+    /// The transform taking global canvas coordinates, relative to `origin`, to viewport
+    /// coordinates.
+    pub fn global_to_viewport(&self) -> Transform {
         if self.zoom_invalid() {
-            panic!("zoom is in an invalid state before converting viewport to global");
+            panic!("zoom is in an invalid state");
         }
 
-        other / CanvasPoint::new(self.zoom, self.zoom) - self.canvas_position
+        Transform::translate(self.translation())
+            .then(Transform::scale(CanvasPoint::splat(self.zoom)))
     }
 
-    pub fn convert_global_to_viewport(&self, other: CanvasPoint) -> CanvasPoint {
+    /// The transform taking viewport coordinates to global canvas coordinates, relative
+    /// to `origin`.
+    pub fn viewport_to_global(&self) -> Transform {
         if self.zoom_invalid() {
-            panic!("zoom is in an invalid state");
+            panic!("zoom is in an invalid state before converting viewport to global");
         }
 
-        (other + self.canvas_position) * CanvasPoint::new(self.zoom, self.zoom)
+        Transform::scale(CanvasPoint::splat(self.zoom.recip()))
+            .then(Transform::translate(-self.translation()))
+    }
+
+    pub fn convert_viewport_to_global(&self, other: CanvasPoint) -> CanvasPoint {
+        self.viewport_to_global().apply(other)
+    }
+
+    pub fn convert_global_to_viewport(&self, other: CanvasPoint) -> CanvasPoint {
+        self.global_to_viewport().apply(other)
     }
 }
 
 impl Default for CameraState {
     fn default() -> Self {
         Self {
-            canvas_position: CanvasPoint::new(0.0, 0.0),
+            canvas_position: WorldPoint::default(),
             zoom: 1.0,
         }
     }
@@ -72,20 +103,16 @@ impl Reducible for CameraState {
             CameraStateAction::MoveCamera {
                 temp_canvas_position,
                 offset,
-            } => {
-                return Self {
-                    canvas_position: temp_canvas_position + offset,
-                    zoom: self.zoom,
-                }
-                .into();
+            } => Self {
+                canvas_position: temp_canvas_position.offset(offset),
+                zoom: self.zoom,
             }
-            CameraStateAction::Refresh => {
-                return Self {
-                    canvas_position: self.canvas_position,
-                    zoom: self.zoom,
-                }
-                .into();
+            .into(),
+            CameraStateAction::Refresh => Self {
+                canvas_position: self.canvas_position,
+                zoom: self.zoom,
             }
+            .into(),
         }
     }
 }
@@ -1,20 +1,34 @@
 use crate::components::GUID_GENERATOR;
 use crate::use_shapes::{ShapeCatalogAction, ShapeCatalogState};
 use crate::{CameraState, CameraStateAction};
-use editor::{get_box, Tool};
-use math::CanvasPoint;
+use editor::{Tool, HIT_TOLERANCE};
+use math::stroke::{StrokePoint, DEFAULT_PRESSURE};
+use math::{Aabb, CanvasPoint, WorldPoint};
 use yew::{hook, Callback, PointerEvent, UseReducerHandle, UseStateHandle};
 
+/// The pen pressure and time of `e` at `position`. Only pens report a meaningful
+/// pressure; for the rest the stroke's width comes from its speed alone.
+fn stroke_sample(e: &PointerEvent, position: CanvasPoint) -> StrokePoint {
+    let pressure = match e.pointer_type().as_str() {
+        "pen" => e.pressure(),
+        _ => DEFAULT_PRESSURE,
+    };
+
+    StrokePoint::new(position, pressure, e.time_stamp())
+}
+
 #[hook]
+#[allow(clippy::too_many_arguments)]
 pub fn use_pointer_down_callback(
     current_tool: Tool,
     camera: UseReducerHandle<CameraState>,
     initial_drag: UseStateHandle<CanvasPoint>,
-    temp_canvas_position: UseStateHandle<CanvasPoint>,
+    temp_canvas_position: UseStateHandle<WorldPoint>,
     global_pointer_down: UseStateHandle<bool>,
     shape_catalog: UseReducerHandle<ShapeCatalogState>,
     active_shape: UseStateHandle<Option<u32>>,
-    selection_box: UseStateHandle<Option<(CanvasPoint, CanvasPoint)>>,
+    selection_box: UseStateHandle<Option<Aabb>>,
+    editing_text: UseStateHandle<Option<u32>>,
 ) -> Callback<PointerEvent> {
     let shape_catalog = shape_catalog.clone();
     let selection_box = selection_box.clone();
@@ -28,14 +42,37 @@ pub fn use_pointer_down_callback(
             initial_drag.set(pointer_position);
             global_pointer_down.set(true);
 
+            // the canvas keeps focus on pointer down, so the text editor is closed here
+            // rather than on blur
+            if let Some(id) = *editing_text {
+                shape_catalog.dispatch(ShapeCatalogAction::FinishText { id });
+                editing_text.set(None);
+            }
+
             match current_tool {
                 Tool::Hand => temp_canvas_position.set((*camera).canvas_position()),
-                Tool::Circle | Tool::Rect | Tool::Freehand => {
+                Tool::Freehand => {
+                    shape_catalog.dispatch(ShapeCatalogAction::UnselectAll);
+                    let next_id = GUID_GENERATOR.next_guid();
+                    shape_catalog.dispatch(ShapeCatalogAction::ExtendFreehand {
+                        id: next_id,
+                        origin: (*camera).origin(),
+                        sample: stroke_sample(&e, global_pointer_position),
+                    });
+                    active_shape.set(Some(next_id));
+                }
+                Tool::Circle
+                | Tool::Ellipse
+                | Tool::Rect
+                | Tool::Line
+                | Tool::Arrow
+                | Tool::Primitive(_) => {
                     shape_catalog.dispatch(ShapeCatalogAction::UnselectAll);
                     let next_id = GUID_GENERATOR.next_guid();
                     shape_catalog.dispatch(ShapeCatalogAction::UpsertShape {
                         id: next_id,
-                        position: pointer_position,
+                        origin: (*camera).origin(),
+                        position: global_pointer_position,
                         width_height: CanvasPoint::new(0.0, 0.0),
                         selected: false,
                         current_tool,
@@ -43,16 +80,46 @@ pub fn use_pointer_down_callback(
                     active_shape.set(Some(next_id));
                 }
                 Tool::Select => {
-                    shape_catalog.dispatch(ShapeCatalogAction::UnselectExceptPoint(
-                        global_pointer_position,
-                    ));
+                    let origin = (*camera).origin();
+                    let tolerance = HIT_TOLERANCE / (*camera).zoom();
+
+                    // the handle still holds the state from before the dispatch below, so
+                    // the click itself decides between dragging shapes and a marquee
+                    let on_shape = shape_catalog
+                        .shape_at(origin, global_pointer_position, tolerance)
+                        .is_some();
 
-                    if shape_catalog.any_selected() {
-                        selection_box.set(Some((pointer_position, CanvasPoint::new(0.0, 0.0))));
+                    shape_catalog.dispatch(ShapeCatalogAction::UnselectExceptPoint {
+                        origin,
+                        point: global_pointer_position,
+                        tolerance,
+                    });
+
+                    if !on_shape {
+                        selection_box.set(Some(Aabb::new(pointer_position, pointer_position)));
                     }
                 }
                 Tool::Text => {
                     shape_catalog.dispatch(ShapeCatalogAction::UnselectAll);
+                    let origin = (*camera).origin();
+
+                    // clicking existing text edits it, and anywhere else starts new text
+                    let id = shape_catalog
+                        .text_at(
+                            origin,
+                            global_pointer_position,
+                            HIT_TOLERANCE / (*camera).zoom(),
+                        )
+                        .unwrap_or_else(|| {
+                            let next_id = GUID_GENERATOR.next_guid();
+                            shape_catalog.dispatch(ShapeCatalogAction::PlaceText {
+                                id: next_id,
+                                origin,
+                                position: global_pointer_position,
+                            });
+                            next_id
+                        });
+                    editing_text.set(Some(id));
                 }
             }
         }
@@ -60,16 +127,17 @@ pub fn use_pointer_down_callback(
 }
 
 #[hook]
+#[allow(clippy::too_many_arguments)]
 pub fn use_pointer_move_callback(
     current_tool: Tool,
     global_pointer_down: UseStateHandle<bool>,
     initial_drag: CanvasPoint,
-    temp_canvas_position: CanvasPoint,
+    temp_canvas_position: WorldPoint,
     camera: UseReducerHandle<CameraState>,
     shape_catalog: UseReducerHandle<ShapeCatalogState>,
     active_shape: UseStateHandle<Option<u32>>,
     client_position: UseStateHandle<Option<(i32, i32)>>,
-    selection_box: UseStateHandle<Option<(CanvasPoint, CanvasPoint)>>,
+    selection_box: UseStateHandle<Option<Aabb>>,
 ) -> Callback<PointerEvent> {
     Callback::from({
         move |e: PointerEvent| {
@@ -86,11 +154,12 @@ pub fn use_pointer_move_callback(
 
                     let camera_state = camera.clone();
 
+                    let origin = (*camera_state).origin();
                     let p1 = (*camera_state).convert_viewport_to_global(initial_drag);
                     let p2 = (*camera_state).convert_viewport_to_global(client_position);
                     let offset = p2 - p1;
 
-                    let (box_position, box_width_height) = get_box(p1, p2);
+                    let dragged_box = Aabb::from_corners(p1, p2);
 
                     match current_tool {
                         Tool::Hand => {
@@ -99,12 +168,13 @@ pub fn use_pointer_move_callback(
                                 offset,
                             });
                         }
-                        Tool::Circle => {
+                        Tool::Circle | Tool::Line | Tool::Arrow => {
                             if let Some(id) = *active_shape {
                                 shape_catalog.dispatch(ShapeCatalogAction::UpsertShape {
                                     id,
+                                    origin,
                                     position: p1,
-                                    width_height: p2,
+                                    width_height: offset,
                                     selected: false,
                                     current_tool,
                                 });
@@ -114,23 +184,22 @@ pub fn use_pointer_move_callback(
                         }
                         Tool::Freehand => {
                             if let Some(id) = *active_shape {
-                                shape_catalog.dispatch(ShapeCatalogAction::UpsertShape {
+                                shape_catalog.dispatch(ShapeCatalogAction::ExtendFreehand {
                                     id,
-                                    position: p2,
-                                    width_height: CanvasPoint::new(0.0, 0.0),
-                                    selected: false,
-                                    current_tool,
+                                    origin,
+                                    sample: stroke_sample(&e, p2),
                                 });
                             } else {
                                 panic!("no active shape");
                             }
                         }
-                        Tool::Rect => {
+                        Tool::Rect | Tool::Ellipse | Tool::Primitive(_) => {
                             if let Some(id) = *active_shape {
                                 shape_catalog.dispatch(ShapeCatalogAction::UpsertShape {
                                     id,
-                                    position: box_position,
-                                    width_height: box_width_height,
+                                    origin,
+                                    position: dragged_box.position(),
+                                    width_height: dragged_box.width_height(),
                                     selected: false,
                                     current_tool,
                                 });
@@ -141,11 +210,12 @@ pub fn use_pointer_move_callback(
                         Tool::Select => {
                             let selection_box = selection_box.clone();
                             match *selection_box {
-                                Some((_, _)) => {
-                                    selection_box.set(Some((box_position, box_width_height)));
+                                Some(_) => {
+                                    selection_box.set(Some(dragged_box));
                                     shape_catalog.dispatch(
                                         ShapeCatalogAction::SelectIntersecting {
-                                            selection_box: ((box_position, box_width_height)),
+                                            origin,
+                                            selection_box: dragged_box,
                                         },
                                     );
                                 }
@@ -165,14 +235,16 @@ pub fn use_pointer_move_callback(
 }
 
 #[hook]
+#[allow(clippy::too_many_arguments)]
 pub fn use_pointer_up_callback(
     current_tool: UseStateHandle<Tool>,
+    simplify_tolerance: f32,
     camera: UseReducerHandle<CameraState>,
-    temp_canvas_position: UseStateHandle<CanvasPoint>,
+    temp_canvas_position: UseStateHandle<WorldPoint>,
     global_pointer_down: UseStateHandle<bool>,
     shape_catalog: UseReducerHandle<ShapeCatalogState>,
     active_shape: UseStateHandle<Option<u32>>,
-    selection_box: UseStateHandle<Option<(CanvasPoint, CanvasPoint)>>,
+    selection_box: UseStateHandle<Option<Aabb>>,
 ) -> Callback<PointerEvent> {
     Callback::from({
         move |e: PointerEvent| {
@@ -182,14 +254,30 @@ pub fn use_pointer_up_callback(
 
             match *current_tool {
                 Tool::Hand => temp_canvas_position.set((*camera).canvas_position()),
-                Tool::Circle | Tool::Rect => {
+                Tool::Circle
+                | Tool::Ellipse
+                | Tool::Rect
+                | Tool::Line
+                | Tool::Arrow
+                | Tool::Primitive(_) => {
+                    if let (Tool::Arrow, Some(id)) = (*current_tool, *active_shape) {
+                        shape_catalog.dispatch(ShapeCatalogAction::BindArrow { id });
+                    }
+                    active_shape.set(None);
+                    shape_catalog.dispatch(ShapeCatalogAction::FinishDrag);
+                }
+                Tool::Freehand => {
+                    if let Some(id) = *active_shape {
+                        shape_catalog.dispatch(ShapeCatalogAction::FinishFreehand {
+                            id,
+                            tolerance: simplify_tolerance / (*camera).zoom(),
+                        });
+                    }
                     active_shape.set(None);
-                    shape_catalog.dispatch(ShapeCatalogAction::SaveSelectedIds);
                 }
-                Tool::Freehand => active_shape.set(None),
                 Tool::Select => {
                     selection_box.set(None);
-                    shape_catalog.dispatch(ShapeCatalogAction::SaveSelectedIds);
+                    shape_catalog.dispatch(ShapeCatalogAction::FinishDrag);
                 }
                 Tool::Text => {}
             }
@@ -1,14 +1,18 @@
-use std::collections::{BTreeMap, BTreeSet};
-
-use editor::{Circle, Freehand, Rectangle, Shape, Tool};
-use math::CanvasPoint;
-use yew::{html, virtual_dom::VNode, Classes, Html, Reducible};
+use editor::{Shape, ShapeCatalog, ShapeGeometry, SvgElement, Text, Tool};
+use math::{boolean::Operation, stroke::StrokePoint, Aabb, CanvasPoint, WorldPoint};
+use yew::virtual_dom::{VNode, VTag, VText};
+use yew::{html, Html, Reducible};
 
 use crate::CameraState;
 
+/// Positions in actions are relative to `origin`, normally the camera's, and offsets and
+/// sizes are plain vectors.
 pub enum ShapeCatalogAction {
+    /// Starts or resizes the shape `id`. Circles are centred on `position` with a radius
+    /// of `width_height`'s length, and ellipses and diagram shapes fill the box it spans.
     UpsertShape {
         id: u32,
+        origin: WorldPoint,
         position: CanvasPoint,
         width_height: CanvasPoint,
         selected: bool,
@@ -18,328 +22,230 @@ pub enum ShapeCatalogAction {
         offset: CanvasPoint,
     },
     SelectIntersecting {
-        selection_box: (CanvasPoint, CanvasPoint),
+        origin: WorldPoint,
+        selection_box: Aabb,
     },
     SelectAll,
     UnselectAll,
     DeleteSelected,
     DeletePrevious,
-    UnselectExceptPoint(CanvasPoint),
-    SaveSelectedIds,
+    RotateSelected {
+        radians: f32,
+    },
+    UnselectExceptPoint {
+        origin: WorldPoint,
+        point: CanvasPoint,
+        tolerance: f32,
+    },
+    /// Ends a drag of the selection, so the next drag starts from where it left off.
+    FinishDrag,
+    /// Appends a pointer sample to the stroke `id`, starting the stroke if it is new.
+    ExtendFreehand {
+        id: u32,
+        origin: WorldPoint,
+        sample: StrokePoint,
+    },
+    FinishFreehand {
+        id: u32,
+        tolerance: f32,
+    },
+    /// Switches the selected arrows between straight and elbow routing.
+    ToggleElbowSelected,
+    /// Binds the ends of the arrow `id` to the shapes they were dropped on.
+    BindArrow {
+        id: u32,
+    },
+    /// Starts empty text `id` at `position`, for typing into.
+    PlaceText {
+        id: u32,
+        origin: WorldPoint,
+        position: CanvasPoint,
+    },
+    EditText {
+        id: u32,
+        content: String,
+    },
+    /// Ends editing the text `id`, dropping it if it was left empty.
+    FinishText {
+        id: u32,
+    },
+    /// Replaces the selected shapes that have an outline with their combination,
+    /// taken in the order they were drawn.
+    CombineSelected {
+        id: u32,
+        operation: Operation,
+        tolerance: f32,
+    },
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct ShapeCatalogState {
-    shapes: BTreeMap<u32, Shape>,
+    catalog: ShapeCatalog,
 }
 
 impl ShapeCatalogState {
     pub fn next_id(&self) -> usize {
-        self.shapes.len()
+        self.catalog.len()
     }
 
     pub fn any_selected(&self) -> bool {
-        self.shapes
-            .iter()
-            .filter(|(_, s)| match s {
-                Shape::Rectangle(r) => r.selected,
-                Shape::Circle(c) => c.selected,
-                Shape::Freehand(f) => f.selected,
-            })
-            .map(|(&id, _)| id)
-            .next()
-            .is_none()
+        self.catalog.any_selected()
     }
 
-    pub fn html(&self, camera: &CameraState) -> VNode {
-        let z = camera.zoom();
-
-        let selected: Classes = format!("stroke-blue-800 stroke-w-[{z}px] fill-green-300").into();
-        let unselected: Classes = "stroke-black stroke-w-1 fill-orange-300".into();
-
-        self.shapes
-            .iter()
-            .map(|(k, s)| {
-                let k = format!("{k}");
-
-                match s {
-                    Shape::Rectangle(r) => {
-                        let path = r.path();
-
-                        let class = if r.selected {
-                            selected.clone()
-                        } else {
-                            unselected.clone()
-                        };
-
-                        html! {
-                            <path key={k} d={path} class={class} />
-                        }
-                    }
-                    Shape::Freehand(f) => {
-                        let points = f
-                            .points
-                            .windows(2)
-                            .flat_map(|p| {
-                                let p1 = p[0];
-                                let p2 = p[1];
-                                let midpoint = p1.midpoint(p2);
-                                let mid_p1 = p1.midpoint(midpoint);
-                                let mid_p2 = midpoint.midpoint(p2);
+    /// How many selected shapes have an outline to combine.
+    pub fn combinable_count(&self, tolerance: f32) -> usize {
+        self.catalog.combinable_count(tolerance)
+    }
 
-                                vec![p1, mid_p1, midpoint, mid_p2, p2].into_iter()
-                            })
-                            .collect::<Vec<CanvasPoint>>();
+    /// The topmost shape under `point`, where `point` is relative to `origin`.
+    pub fn shape_at(&self, origin: WorldPoint, point: CanvasPoint, tolerance: f32) -> Option<u32> {
+        self.catalog.shape_at(origin, point, tolerance)
+    }
 
-                        let points = points
-                            .windows(3)
-                            .map(|p| {
-                                let p1 = p[0];
-                                let p2 = p[1];
-                                let p3 = p[2];
+    /// The topmost text under `point`, where `point` is relative to `origin`.
+    pub fn text_at(&self, origin: WorldPoint, point: CanvasPoint, tolerance: f32) -> Option<u32> {
+        self.shape_at(origin, point, tolerance)
+            .filter(|&id| matches!(self.catalog.get(id), Some(Shape::Text(_))))
+    }
 
-                                let point = (p1 + p2 + p3) / CanvasPoint::new(3.0, 3.0);
+    /// The text `id` and the top-left corner of its box relative to `origin`.
+    pub fn text(&self, id: u32, origin: WorldPoint) -> Option<(CanvasPoint, &Text)> {
+        match self.catalog.get(id)? {
+            Shape::Text(t) => {
+                let anchor = self.catalog.anchor(id)?;
 
-                                point
-                            })
-                            .collect::<Vec<CanvasPoint>>();
+                Some((t.position + anchor.relative_to(origin), t))
+            }
+            _ => None,
+        }
+    }
 
-                        let line = points
-                            .windows(2)
-                            .map(|p| {
-                                let p1 = p[0];
-                                let p2 = p[1];
+    /// Renders the shapes whose bounds touch `visible`, in global coordinates relative to
+    /// the camera's origin, in their own styles. The selected shapes are then outlined on
+    /// top, one screen pixel wide whatever the zoom. The shape `hidden`, such as text being
+    /// edited in place, is laid out but not drawn.
+    pub fn html(&self, camera: &CameraState, visible: Aabb, hidden: Option<u32>) -> VNode {
+        let selection_width = 1.5 / camera.zoom();
+        let shapes: Vec<_> = self.catalog.visible(camera.origin(), visible).collect();
 
-                                let (start_x, start_y) = p1.coord();
-                                let (end_x, end_y) = p2.coord();
+        let drawn = shapes
+            .iter()
+            .map(|&(id, offset, s)| {
+                let (dx, dy) = offset.coord();
+                let k = format!("{id}");
 
-                                html! {
-                                    <line x1={start_x.to_string()} y1={start_y.to_string()} x2={end_x.to_string()} y2={end_y.to_string()} stroke="black" stroke-width="4" stroke-linecap="round" />
-                                }
-                            })
-                            .collect::<Html>();
+                let mut shape = vtag(s.render());
+                if hidden == Some(id) {
+                    shape.add_attribute("visibility", "hidden");
+                }
 
-                        html! {
-                            <g key={k}>
-                                {line}
-                            </g>
-                        }
-                    }
-                    Shape::Circle(c) => {
-                        let (x, y) = c.center.coord();
-                        let r = format!("{}", c.radius);
-                        let class = if c.selected {
-                            selected.clone()
-                        } else {
-                            unselected.clone()
-                        };
+                html! {
+                    <g key={k} transform={format!("translate({dx} {dy})")}>{VNode::from(shape)}</g>
+                }
+            })
+            .collect::<Html>();
 
-                        html! {
-                            <circle class={class} cx={format!("{x}")} cy={format!("{y}")} r={r} />
-                        }
-                    }
+        let selection = shapes
+            .iter()
+            .filter(|(_, _, s)| s.is_selected())
+            .map(|&(id, offset, s)| {
+                let (dx, dy) = offset.coord();
+                let k = format!("{id}");
+
+                html! {
+                    <path
+                        key={k}
+                        transform={format!("translate({dx} {dy})")}
+                        d={s.to_path()}
+                        class="stroke-blue-800"
+                        fill="none"
+                        stroke-width={selection_width.to_string()}
+                    />
                 }
             })
-            .collect::<Html>()
+            .collect::<Html>();
+
+        html! {
+            <>
+                <g id="shapes">{drawn}</g>
+                <g id="selection" class="pointer-events-none">{selection}</g>
+            </>
+        }
     }
 }
 
-impl Default for ShapeCatalogState {
-    fn default() -> Self {
-        Self {
-            shapes: BTreeMap::new(),
-        }
+fn vtag(element: SvgElement) -> VTag {
+    let mut tag = VTag::new(element.tag);
+    for (name, value) in element.attributes {
+        tag.add_attribute(name, value);
+    }
+    if let Some(text) = element.text {
+        tag.add_child(VText::new(text).into());
     }
+    for child in element.children {
+        tag.add_child(vtag(child).into());
+    }
+
+    tag
 }
 
 impl Reducible for ShapeCatalogState {
     type Action = ShapeCatalogAction;
 
     fn reduce(self: std::rc::Rc<Self>, action: Self::Action) -> std::rc::Rc<Self> {
-        let mut shapes = self.shapes.clone();
+        let mut catalog = self.catalog.clone();
         match action {
             ShapeCatalogAction::UpsertShape {
                 id,
+                origin,
                 position,
                 width_height,
                 selected,
                 current_tool,
-            } => {
-                if let Some(shape) = shapes.get_mut(&id) {
-                    match shape {
-                        Shape::Rectangle(rectangle) => {
-                            // Update the existing shape
-                            rectangle.position = position;
-                            rectangle.width_height = width_height;
-                            rectangle.selected = selected;
-                        }
-                        Shape::Circle(circle) => {
-                            circle.center = position;
-                            circle.radius = position.euclid_dist(width_height);
-                            circle.selected = selected;
-                        }
-                        Shape::Freehand(f) => {
-                            f.points.push(position);
-                            f.selected = selected;
-                        }
-                    }
-                } else {
-                    let new_shape = match current_tool {
-                        Tool::Circle => {
-                            let circle = Circle::new(position, 0.0, selected);
-
-                            Shape::Circle(circle)
-                        }
-                        Tool::Rect => {
-                            let rectangle = Rectangle::new(position, width_height, selected);
-                            Shape::Rectangle(rectangle)
-                        }
-                        Tool::Freehand => Shape::Freehand(Freehand::new(position, selected)),
-                        _ => panic!("unallowed tool"),
-                    };
-
-                    shapes.insert(id, new_shape);
-                }
-            }
-            ShapeCatalogAction::UnselectAll => {
-                // Iterate and unselect all shapes
-                for (_, s) in shapes.iter_mut() {
-                    match s {
-                        Shape::Rectangle(r) => r.selected = false,
-                        Shape::Circle(c) => c.selected = false,
-                        Shape::Freehand(f) => f.selected = false,
-                    }
-                }
-            }
-            ShapeCatalogAction::UnselectExceptPoint(point) => {
-                // new_selection is a flag to check whether there is a new selection box.
-                // if a new selection box is created, first unselect all selected shapes.
-                let mut new_selection = BTreeSet::new();
-                let mut not_inside_any_shapes = true;
-                for (shape_id, s) in shapes.iter_mut() {
-                    match s {
-                        Shape::Rectangle(r) => {
-                            if r.is_inside(point) {
-                                if !r.selected {
-                                    r.selected = true;
-                                    new_selection.insert(*shape_id);
-                                }
-
-                                not_inside_any_shapes = false;
-                            }
-                        }
-                        Shape::Circle(c) => {
-                            if c.is_inside(point) {
-                                if !c.selected {
-                                    c.selected = true;
-                                    new_selection.insert(*shape_id);
-                                }
-
-                                not_inside_any_shapes = false;
-                            }
-                        }
-                        Shape::Freehand(_f) => {
-                            // todo! implement freehand is inside
-                        }
-                    }
-                }
-
-                if not_inside_any_shapes {
-                    for (_, s) in shapes.iter_mut() {
-                        match s {
-                            Shape::Rectangle(r) => r.selected = false,
-                            Shape::Circle(c) => c.selected = false,
-                            Shape::Freehand(f) => f.selected = false,
-                        }
-                    }
-                }
-
-                if !new_selection.is_empty() {
-                    for (shape_id, s) in shapes.iter_mut() {
-                        match s {
-                            Shape::Rectangle(r) => r.selected = new_selection.contains(shape_id),
-                            Shape::Circle(c) => c.selected = new_selection.contains(shape_id),
-                            Shape::Freehand(f) => f.selected = new_selection.contains(shape_id),
-                        }
-                    }
-                }
-            }
-            ShapeCatalogAction::UpsertSelectedShapes { offset } => {
-                for (_, s) in shapes.iter_mut() {
-                    match s {
-                        Shape::Rectangle(r) => {
-                            if r.selected {
-                                let temp_position = if let Some(tp) = r.temp_position {
-                                    tp
-                                } else {
-                                    r.position
-                                };
-
-                                r.position = temp_position + offset;
-                            }
-                        }
-                        Shape::Circle(c) => {
-                            let temp_center = if let Some(tp) = c.temp_center {
-                                tp
-                            } else {
-                                c.center
-                            };
-
-                            c.center = temp_center + offset;
-                        }
-                        Shape::Freehand(_f) => {}
-                    }
-                }
-            }
-            ShapeCatalogAction::SelectIntersecting { selection_box } => {
-                for (_, s) in shapes.iter_mut() {
-                    match s {
-                        Shape::Rectangle(r) => r.selected = r.intersects(selection_box),
-                        Shape::Circle(c) => c.selected = c.intersects(selection_box),
-                        Shape::Freehand(f) => f.selected = f.intersects(selection_box),
-                    }
-                }
-            }
-            ShapeCatalogAction::SaveSelectedIds => {
-                for (_, s) in shapes.iter_mut() {
-                    match s {
-                        Shape::Rectangle(r) => {
-                            if r.selected {
-                                r.temp_position = Some(r.position);
-                            }
-                        }
-                        Shape::Circle(c) => {
-                            if c.selected {
-                                c.temp_center = Some(c.center);
-                            }
-                        }
-                        Shape::Freehand(f) => {
-                            if f.selected {
-                                todo!();
-                            }
-                        }
-                    }
-                }
-            }
-            ShapeCatalogAction::SelectAll => {
-                for (_, s) in shapes.iter_mut() {
-                    match s {
-                        Shape::Rectangle(r) => r.selected = true,
-                        Shape::Circle(c) => c.selected = true,
-                        Shape::Freehand(f) => f.selected = true,
-                    }
-                }
+            } => catalog.upsert(id, origin, position, |position| {
+                Shape::from_tool(current_tool, position, width_height, selected)
+                    .expect("unallowed tool")
+            }),
+            ShapeCatalogAction::UnselectAll => catalog.unselect_all(),
+            ShapeCatalogAction::UnselectExceptPoint {
+                origin,
+                point,
+                tolerance,
+            } => catalog.select_at(origin, point, tolerance),
+            ShapeCatalogAction::UpsertSelectedShapes { offset } => catalog.drag_selected(offset),
+            ShapeCatalogAction::SelectIntersecting {
+                origin,
+                selection_box,
+            } => catalog.select_intersecting(origin, selection_box),
+            ShapeCatalogAction::FinishDrag => catalog.finish_drag(),
+            ShapeCatalogAction::RotateSelected { radians } => catalog.rotate_selected(radians),
+            ShapeCatalogAction::SelectAll => catalog.select_all(),
+            ShapeCatalogAction::DeleteSelected => catalog.delete_selected(),
+            ShapeCatalogAction::DeletePrevious => {}
+            ShapeCatalogAction::ExtendFreehand { id, origin, sample } => {
+                catalog.extend_freehand(id, origin, sample)
             }
-            ShapeCatalogAction::DeleteSelected => {
-                shapes.retain(|_, s| match s {
-                    Shape::Rectangle(r) => !r.selected,
-                    Shape::Circle(c) => !c.selected,
-                    Shape::Freehand(f) => !f.selected,
-                });
+            ShapeCatalogAction::FinishFreehand { id, tolerance } => {
+                catalog.finish_freehand(id, tolerance)
             }
-            ShapeCatalogAction::DeletePrevious => {}
+            ShapeCatalogAction::BindArrow { id } => catalog.bind_arrow(id),
+            ShapeCatalogAction::ToggleElbowSelected => catalog.toggle_elbow_selected(),
+            ShapeCatalogAction::PlaceText {
+                id,
+                origin,
+                position,
+            } => catalog.upsert(id, origin, position, |position| {
+                Shape::Text(Text::empty(position, false))
+            }),
+            ShapeCatalogAction::EditText { id, content } => catalog.edit_text(id, content),
+            ShapeCatalogAction::FinishText { id } => catalog.finish_text(id),
+            ShapeCatalogAction::CombineSelected {
+                id,
+                operation,
+                tolerance,
+            } => catalog.combine_selected(id, operation, tolerance),
         }
 
-        ShapeCatalogState { shapes }.into()
+        ShapeCatalogState { catalog }.into()
     }
 }
@@ -0,0 +1,957 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use math::boolean::Operation;
+use math::route::{self, Endpoint};
+use math::stroke::StrokePoint;
+use math::{Aabb, CanvasPoint, WorldPoint};
+
+use crate::{
+    Arrow, Binding, Compound, Freehand, Line, Routing, Shape, ShapeGeometry, SpatialIndex,
+    ROUTE_MARGIN,
+};
+
+/// Every shape on the canvas, with the selection and the edits made to it.
+///
+/// Each shape is stored relative to its own anchor, the corner of the chunk it was
+/// started in, so its `f32` coordinates stay small wherever it is on the canvas. Positions
+/// passed in are relative to an `origin`, normally the camera's, and offsets are plain
+/// vectors.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ShapeCatalog {
+    shapes: BTreeMap<u32, Shape>,
+    /// The anchor of every shape, kept in step with `shapes`.
+    anchors: BTreeMap<u32, WorldPoint>,
+    /// The bounds of every shape in world coordinates, kept in step with `shapes`. See
+    /// [`index_bounds`].
+    index: SpatialIndex,
+    /// The selected shapes as they were when the current drag began.
+    dragged: BTreeMap<u32, Shape>,
+}
+
+/// `bounds`, relative to `anchor`, in world coordinates for the spatial index. Far from
+/// the origin `f32` cannot hold them exactly, so they are rounded outwards by a unit in
+/// the last place. The index only narrows down candidates for the exact tests, which run
+/// relative to the anchor.
+fn index_bounds(anchor: WorldPoint, bounds: Aabb) -> Aabb {
+    let world = |p: CanvasPoint| anchor.offset(p).relative_to(WorldPoint::default());
+    let bounds = Aabb::new(world(bounds.min), world(bounds.max));
+    let magnitude = bounds.min.abs().max(bounds.max.abs());
+
+    bounds.expand(magnitude.one().max(magnitude.two()) * f32::EPSILON)
+}
+
+impl ShapeCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Shape> {
+        self.shapes.get(&id)
+    }
+
+    pub fn anchor(&self, id: u32) -> Option<WorldPoint> {
+        self.anchors.get(&id).copied()
+    }
+
+    pub fn any_selected(&self) -> bool {
+        self.shapes.values().any(Shape::is_selected)
+    }
+
+    pub fn selected_ids(&self) -> Vec<u32> {
+        self.shapes
+            .iter()
+            .filter(|(_, s)| s.is_selected())
+            .map(|(&id, _)| id)
+            .collect()
+    }
+
+    /// The shapes whose bounds touch `rect`, in drawing order, each with its anchor's
+    /// offset from `origin`.
+    pub fn visible(
+        &self,
+        origin: WorldPoint,
+        rect: Aabb,
+    ) -> impl Iterator<Item = (u32, CanvasPoint, &Shape)> + '_ {
+        self.index
+            .query_rect(index_bounds(origin, rect))
+            .into_iter()
+            .filter_map(move |id| {
+                let offset = self.anchors[&id].relative_to(origin);
+                self.shapes.get(&id).map(|s| (id, offset, s))
+            })
+    }
+
+    fn reindex(&mut self, id: u32) {
+        if let (Some(s), Some(&anchor)) = (self.shapes.get(&id), self.anchors.get(&id)) {
+            self.index.insert(id, index_bounds(anchor, s.bounds()));
+        }
+    }
+
+    fn remove(&mut self, id: u32) -> Option<Shape> {
+        self.anchors.remove(&id);
+        self.index.remove(id);
+        self.dragged.remove(&id);
+
+        for s in self.shapes.values_mut() {
+            if let Shape::Arrow(a) = s {
+                a.unbind(id);
+            }
+        }
+
+        self.shapes.remove(&id)
+    }
+
+    /// The binding for an end of the arrow `id` at `point`, relative to the arrow's anchor,
+    /// to the topmost shape there that arrows can bind to.
+    fn binding_at(&self, id: u32, point: CanvasPoint) -> Option<Binding> {
+        let origin = self.anchors[&id];
+
+        self.index
+            .query_rect(index_bounds(origin, Aabb::new(point, point)))
+            .into_iter()
+            .filter(|&target| target != id)
+            .filter_map(|target| {
+                let point = point + origin.relative_to(self.anchors[&target]);
+                let s = &self.shapes[&target];
+
+                s.hit_test(point, 0.0)
+                    .then(|| s.anchor_at(point))
+                    .flatten()
+                    .map(|anchor| Binding { target, anchor })
+            })
+            .max_by_key(|b| b.target)
+    }
+
+    /// Binds each end of the arrow `id` to the shape it was dropped on, if arrows can bind
+    /// to it, and unbinds ends dropped on empty canvas.
+    pub fn bind_arrow(&mut self, id: u32) {
+        let Some(Shape::Arrow(a)) = self.shapes.get(&id) else {
+            return;
+        };
+        let start = self.binding_at(id, a.line.start());
+        let end = self.binding_at(id, a.line.end());
+
+        if let Some(Shape::Arrow(a)) = self.shapes.get_mut(&id) {
+            a.start_binding = start;
+            a.end_binding = end;
+        }
+
+        self.route_arrow(id);
+    }
+
+    /// The bounds of shape `id` relative to `origin`.
+    fn bounds_relative_to(&self, id: u32, origin: WorldPoint) -> Option<Aabb> {
+        let s = self.shapes.get(&id)?;
+
+        Some(s.bounds().translate(self.anchors[&id].relative_to(origin)))
+    }
+
+    /// Routes the elbow arrow `id` from end to end around the shapes in the way, leaving
+    /// and entering the shapes its ends are bound to at right angles. Other lines and
+    /// arrows are not in the way.
+    fn route_arrow(&mut self, id: u32) {
+        let Some(Shape::Arrow(a)) = self.shapes.get(&id) else {
+            return;
+        };
+        if a.routing != Routing::Elbow {
+            return;
+        }
+
+        let origin = self.anchors[&id];
+        let endpoint = |point: CanvasPoint, binding: Option<Binding>| {
+            let bound = binding.and_then(|b| {
+                Some(Endpoint::on(
+                    self.bound_point(b, origin)?,
+                    self.bounds_relative_to(b.target, origin)?,
+                ))
+            });
+
+            bound.unwrap_or(Endpoint::free(point))
+        };
+        let start = endpoint(a.line.start(), a.start_binding);
+        let end = endpoint(a.line.end(), a.end_binding);
+        let targets = [a.start_binding, a.end_binding].map(|b| b.map(|b| b.target));
+
+        let obstacles_in = |region: Aabb| -> Vec<Aabb> {
+            self.index
+                .query_rect(index_bounds(origin, region))
+                .into_iter()
+                .filter(|&other| {
+                    other != id
+                        && !targets.contains(&Some(other))
+                        && !matches!(self.shapes[&other], Shape::Line(_) | Shape::Arrow(_))
+                })
+                .filter_map(|other| self.bounds_relative_to(other, origin))
+                .collect()
+        };
+
+        // only shapes near the ends are looked up at first, and the search widens to take
+        // in everything near the route whenever it strays outside what was looked up
+        let mut region = [start, end]
+            .map(|e| e.around.unwrap_or(Aabb::new(e.point, e.point)))
+            .into_iter()
+            .reduce(|a, b| a.union(b))
+            .expect("an arrow has two ends")
+            .expand(ROUTE_MARGIN * 4.0);
+        let mut obstacles = obstacles_in(region);
+        let points = loop {
+            let points = route::orthogonal(start, end, &obstacles, ROUTE_MARGIN);
+            let Some(reach) = Aabb::from_points(&points).map(|b| b.expand(ROUTE_MARGIN)) else {
+                return;
+            };
+            if region.contains_aabb(reach) {
+                break points;
+            }
+
+            region = region.union(reach).expand(ROUTE_MARGIN * 4.0);
+            let widened = obstacles_in(region);
+            if widened.len() == obstacles.len() {
+                break points;
+            }
+            obstacles = widened;
+        };
+        if points.len() < 2 {
+            return;
+        }
+
+        if let Some(Shape::Arrow(a)) = self.shapes.get_mut(&id) {
+            a.line.points = points;
+        }
+        self.reindex(id);
+    }
+
+    /// The indexed bounds of each of `ids` that is on the canvas.
+    fn indexed(&self, ids: &[u32]) -> Vec<Aabb> {
+        ids.iter().filter_map(|&id| self.index.get(id)).collect()
+    }
+
+    /// Where shapes can change the route of the elbow arrow `id`, in index coordinates:
+    /// around the route and the shapes its ends are bound to, as far as
+    /// [`ShapeCatalog::route_arrow`] first looks for obstacles.
+    fn route_region(&self, id: u32, a: &Arrow) -> Option<Aabb> {
+        [
+            Some(id),
+            a.start_binding.map(|b| b.target),
+            a.end_binding.map(|b| b.target),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|id| self.index.get(id))
+        .reduce(|a, b| a.union(b))
+        .map(|region| region.expand(ROUTE_MARGIN * 4.0))
+    }
+
+    /// Reroutes the elbow arrows that shapes in `changed`, the index bounds of shapes from
+    /// before and after they changed, may have moved into or out of the way of.
+    fn reroute(&mut self, changed: &[Aabb]) {
+        let elbows: Vec<u32> = self
+            .shapes
+            .iter()
+            .filter(|&(&id, s)| match s {
+                Shape::Arrow(a) if a.routing == Routing::Elbow => self
+                    .route_region(id, a)
+                    .is_some_and(|region| changed.iter().any(|b| region.intersects(*b))),
+                _ => false,
+            })
+            .map(|(&id, _)| id)
+            .collect();
+
+        for id in elbows {
+            self.route_arrow(id);
+        }
+    }
+
+    /// Switches the selected arrows between straight and elbow routing. Straightened
+    /// arrows drop their bends.
+    pub fn toggle_elbow_selected(&mut self) {
+        for id in self.selected_ids() {
+            let Some(Shape::Arrow(a)) = self.shapes.get_mut(&id) else {
+                continue;
+            };
+
+            match a.routing {
+                Routing::Straight => a.routing = Routing::Elbow,
+                Routing::Elbow => {
+                    a.routing = Routing::Straight;
+                    a.line.points = Line::straight(a.line.start(), a.line.end(), false).points;
+                }
+            }
+
+            self.route_arrow(id);
+            self.reindex(id);
+        }
+    }
+
+    /// `binding`'s point relative to `origin`, or `None` if its target cannot be bound to.
+    fn bound_point(&self, binding: Binding, origin: WorldPoint) -> Option<CanvasPoint> {
+        let point = self
+            .shapes
+            .get(&binding.target)?
+            .anchor_point(binding.anchor)?;
+
+        Some(point + self.anchors[&binding.target].relative_to(origin))
+    }
+
+    /// Moves the ends of arrows bound to any of `targets` back onto their anchors, then
+    /// reroutes the elbow arrows near the `changed` bounds, as in [`ShapeCatalog::reroute`].
+    fn follow(&mut self, targets: &BTreeSet<u32>, changed: &[Aabb]) {
+        let bound_to_targets = |b: &Option<Binding>| b.is_some_and(|b| targets.contains(&b.target));
+        let arrows: Vec<u32> = self
+            .shapes
+            .iter()
+            .filter(|(_, s)| {
+                matches!(s, Shape::Arrow(a)
+                    if bound_to_targets(&a.start_binding) || bound_to_targets(&a.end_binding))
+            })
+            .map(|(&id, _)| id)
+            .collect();
+
+        for id in arrows {
+            let Some(Shape::Arrow(a)) = self.shapes.get(&id) else {
+                continue;
+            };
+            let origin = self.anchors[&id];
+            let start = a.start_binding.and_then(|b| self.bound_point(b, origin));
+            let end = a.end_binding.and_then(|b| self.bound_point(b, origin));
+
+            if let Some(Shape::Arrow(a)) = self.shapes.get_mut(&id) {
+                if let Some(start) = start {
+                    a.line.set_start(start);
+                }
+                if let Some(end) = end {
+                    a.line.set_end(end);
+                }
+            }
+            self.reindex(id);
+        }
+
+        self.reroute(changed);
+    }
+
+    /// Puts `build`'s shape under `id`, replacing any shape already there. `build` is
+    /// given `position` relative to the shape's anchor, which is chosen from `position`
+    /// the first time `id` is seen.
+    pub fn upsert(
+        &mut self,
+        id: u32,
+        origin: WorldPoint,
+        position: CanvasPoint,
+        build: impl FnOnce(CanvasPoint) -> Shape,
+    ) {
+        let anchor = *self
+            .anchors
+            .entry(id)
+            .or_insert_with(|| origin.offset(position).chunk());
+
+        let mut changed = self.indexed(&[id]);
+        self.shapes
+            .insert(id, build(position + origin.relative_to(anchor)));
+        self.reindex(id);
+
+        changed.extend(self.indexed(&[id]));
+        self.reroute(&changed);
+    }
+
+    /// Appends a pointer sample to the stroke `id`, starting the stroke if it is new.
+    pub fn extend_freehand(&mut self, id: u32, origin: WorldPoint, sample: StrokePoint) {
+        let anchor = *self
+            .anchors
+            .entry(id)
+            .or_insert_with(|| origin.offset(sample.point).chunk());
+        let sample = StrokePoint {
+            point: sample.point + origin.relative_to(anchor),
+            ..sample
+        };
+
+        match self.shapes.get_mut(&id) {
+            Some(Shape::Freehand(f)) => f.push(sample),
+            Some(_) => return,
+            None => {
+                self.shapes
+                    .insert(id, Shape::Freehand(Freehand::from_sample(sample, false)));
+            }
+        }
+
+        self.reindex(id);
+    }
+
+    pub fn finish_freehand(&mut self, id: u32, tolerance: f32) {
+        if let Some(Shape::Freehand(f)) = self.shapes.get_mut(&id) {
+            f.finish(tolerance);
+            self.reindex(id);
+        }
+    }
+
+    /// Replaces the content of the text `id`, resizing its box to fit.
+    pub fn edit_text(&mut self, id: u32, content: String) {
+        if let Some(Shape::Text(t)) = self.shapes.get_mut(&id) {
+            t.set_content(content);
+            self.reindex(id);
+        }
+    }
+
+    /// Ends editing the text `id`, removing it if nothing but whitespace was typed.
+    pub fn finish_text(&mut self, id: u32) {
+        if let Some(Shape::Text(t)) = self.shapes.get(&id) {
+            if t.content.trim().is_empty() {
+                self.remove(id);
+            }
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.shapes.values_mut().for_each(|s| s.set_selected(true));
+    }
+
+    pub fn unselect_all(&mut self) {
+        self.shapes.values_mut().for_each(|s| s.set_selected(false));
+    }
+
+    /// The topmost shape under `point`, where strokes are hit within `tolerance`.
+    pub fn shape_at(&self, origin: WorldPoint, point: CanvasPoint, tolerance: f32) -> Option<u32> {
+        self.index
+            .query_rect(index_bounds(
+                origin,
+                Aabb::new(point, point).expand(tolerance),
+            ))
+            .into_iter()
+            .filter(|id| {
+                let point = point + origin.relative_to(self.anchors[id]);
+
+                self.shapes[id].hit_test(point, tolerance)
+            })
+            .max()
+    }
+
+    /// Handles a click at `point`. Clicking an unselected shape selects just the shapes
+    /// under the pointer, clicking a selected one keeps the selection so it can be
+    /// dragged, and clicking empty canvas clears it. Strokes are hit within `tolerance`.
+    pub fn select_at(&mut self, origin: WorldPoint, point: CanvasPoint, tolerance: f32) {
+        let candidates = self.index.query_rect(index_bounds(
+            origin,
+            Aabb::new(point, point).expand(tolerance),
+        ));
+
+        let mut new_selection = BTreeSet::new();
+        let mut not_inside_any_shapes = true;
+        for id in candidates {
+            let Some(s) = self.shapes.get_mut(&id) else {
+                continue;
+            };
+            let point = point + origin.relative_to(self.anchors[&id]);
+
+            if s.hit_test(point, tolerance) {
+                if !s.is_selected() {
+                    s.set_selected(true);
+                    new_selection.insert(id);
+                }
+
+                not_inside_any_shapes = false;
+            }
+        }
+
+        if not_inside_any_shapes {
+            self.unselect_all();
+        }
+
+        if !new_selection.is_empty() {
+            for (id, s) in self.shapes.iter_mut() {
+                s.set_selected(new_selection.contains(id));
+            }
+        }
+    }
+
+    /// Selects exactly the shapes that touch `rect`. Only the shapes the index finds near
+    /// it are tested, and the rest of the previous selection is cleared.
+    pub fn select_intersecting(&mut self, origin: WorldPoint, rect: Aabb) {
+        let candidates = self.index.query_rect(index_bounds(origin, rect));
+
+        for id in self.selected_ids() {
+            if let Some(s) = self.shapes.get_mut(&id) {
+                s.set_selected(false);
+            }
+        }
+
+        for id in candidates {
+            let Some(s) = self.shapes.get_mut(&id) else {
+                continue;
+            };
+            let rect = rect.translate(origin.relative_to(self.anchors[&id]));
+
+            s.set_selected(s.intersects_rect(rect));
+        }
+    }
+
+    /// Moves the selection to `offset` from where it was when the drag began. Arrows bound
+    /// to the moved shapes follow them.
+    pub fn drag_selected(&mut self, offset: CanvasPoint) {
+        let selected = self.selected_ids();
+        let mut changed = self.indexed(&selected);
+        for &id in &selected {
+            let s = self.shapes.get_mut(&id).expect("selected ids exist");
+            let start = self.dragged.entry(id).or_insert_with(|| s.clone());
+
+            *s = start.clone();
+            s.translate(offset);
+            self.reindex(id);
+        }
+
+        changed.extend(self.indexed(&selected));
+        self.follow(&selected.into_iter().collect(), &changed);
+    }
+
+    /// Ends a drag, so the next one starts from where this one left off. Dragged arrows
+    /// are bound to whatever their ends were dropped on.
+    pub fn finish_drag(&mut self) {
+        let dragged = std::mem::take(&mut self.dragged);
+
+        for id in dragged.into_keys() {
+            self.bind_arrow(id);
+        }
+    }
+
+    pub fn rotate_selected(&mut self, radians: f32) {
+        let selected = self.selected_ids();
+        let mut changed = self.indexed(&selected);
+        for &id in &selected {
+            if let Some(s) = self.shapes.get_mut(&id) {
+                s.rotate(radians);
+            }
+            self.reindex(id);
+        }
+
+        changed.extend(self.indexed(&selected));
+        self.follow(&selected.into_iter().collect(), &changed);
+    }
+
+    pub fn delete_selected(&mut self) {
+        let selected = self.selected_ids();
+        let changed = self.indexed(&selected);
+        for id in selected {
+            self.remove(id);
+        }
+
+        self.reroute(&changed);
+    }
+
+    /// How many selected shapes have an outline, and so would take part in
+    /// [`ShapeCatalog::combine_selected`].
+    pub fn combinable_count(&self, tolerance: f32) -> usize {
+        self.combinable_ids(tolerance).len()
+    }
+
+    fn combinable_ids(&self, tolerance: f32) -> Vec<u32> {
+        self.shapes
+            .iter()
+            .filter(|(_, s)| s.is_selected() && s.outline(tolerance).is_some())
+            .map(|(&id, _)| id)
+            .collect()
+    }
+
+    /// Replaces the selected shapes that have an outline with their combination under
+    /// `id`, taken in the order they were drawn.
+    pub fn combine_selected(&mut self, id: u32, operation: Operation, tolerance: f32) {
+        let operands = self.combinable_ids(tolerance);
+
+        // the compound takes the first operand's anchor, and the others are moved into
+        // its frame
+        let Some(&anchor) = operands.first().map(|first| &self.anchors[first]) else {
+            return;
+        };
+        let moved: Vec<Shape> = operands
+            .iter()
+            .map(|id| {
+                let mut s = self.shapes[id].clone();
+                s.translate(self.anchors[id].relative_to(anchor));
+                s
+            })
+            .collect();
+
+        if let Some(compound) = Compound::combine(operation, moved.iter(), tolerance) {
+            let mut changed = self.indexed(&operands);
+            for operand in operands {
+                self.remove(operand);
+            }
+
+            self.anchors.insert(id, anchor);
+            self.shapes.insert(id, Shape::Compound(compound));
+            self.reindex(id);
+
+            changed.extend(self.indexed(&[id]));
+            self.reroute(&changed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::{Arrow, Rectangle, Text, Tool};
+
+    fn p(x: f32, y: f32) -> CanvasPoint {
+        CanvasPoint::new(x, y)
+    }
+
+    /// A finished stroke from (0, 0) to (100, 0) under id 1 and a 10 × 10 square at
+    /// (50, 50) under id 2.
+    fn catalog(origin: WorldPoint) -> ShapeCatalog {
+        let mut catalog = ShapeCatalog::new();
+
+        for (i, x) in [0.0, 50.0, 100.0].into_iter().enumerate() {
+            let sample = StrokePoint::new(p(x, 0.0), 0.5, i as f64 * 16.0);
+            catalog.extend_freehand(1, origin, sample);
+        }
+        catalog.finish_freehand(1, 0.5);
+
+        catalog.upsert(2, origin, p(50.0, 50.0), |position| {
+            Shape::from_tool(Tool::Rect, position, p(10.0, 10.0), false).unwrap()
+        });
+
+        catalog
+    }
+
+    /// Upserts a `size` rectangle at `at` under `id`, relative to the world origin.
+    fn rect(c: &mut ShapeCatalog, id: u32, at: CanvasPoint, size: CanvasPoint) {
+        c.upsert(id, WorldPoint::default(), at, |position| {
+            Shape::from_tool(Tool::Rect, position, size, false).unwrap()
+        });
+    }
+
+    fn arrow(c: &ShapeCatalog, id: u32) -> &Arrow {
+        match c.get(id) {
+            Some(Shape::Arrow(a)) => a,
+            _ => panic!("the arrow is still an arrow"),
+        }
+    }
+
+    #[test]
+    fn click_freehand() {
+        let origin = WorldPoint::default();
+        let mut c = catalog(origin);
+
+        // a click near the stroke picks it, within the tolerance only
+        c.select_at(origin, p(30.0, 5.0), 4.0);
+        assert_eq!(c.selected_ids(), Vec::<u32>::new());
+
+        c.select_at(origin, p(30.0, 3.0), 4.0);
+        assert_eq!(c.selected_ids(), vec![1]);
+
+        // clicking the square moves the selection to it
+        c.select_at(origin, p(55.0, 55.0), 4.0);
+        assert_eq!(c.selected_ids(), vec![2]);
+
+        c.select_at(origin, p(200.0, 200.0), 4.0);
+        assert!(!c.any_selected());
+    }
+
+    #[test]
+    fn marquee_freehand() {
+        let origin = WorldPoint::default();
+        let mut c = catalog(origin);
+
+        // the box crosses the stroke between its points
+        c.select_intersecting(origin, Aabb::new(p(20.0, -5.0), p(30.0, 5.0)));
+        assert_eq!(c.selected_ids(), vec![1]);
+
+        c.select_intersecting(origin, Aabb::new(p(20.0, 1.0), p(30.0, 5.0)));
+        assert!(!c.any_selected());
+
+        c.select_intersecting(origin, Aabb::new(p(40.0, -5.0), p(55.0, 55.0)));
+        assert_eq!(c.selected_ids(), vec![1, 2]);
+    }
+
+    #[test]
+    fn drag_freehand_with_selection() {
+        let origin = WorldPoint::default();
+        let mut c = catalog(origin);
+        let before = [1, 2].map(|id| c.get(id).unwrap().bounds());
+
+        c.select_all();
+        c.drag_selected(p(5.0, 5.0));
+        c.drag_selected(p(10.0, 20.0));
+
+        // each step moves from where the drag began, not from the last step
+        for (id, bounds) in [1, 2].into_iter().zip(before) {
+            assert_eq!(c.get(id).unwrap().bounds(), bounds.translate(p(10.0, 20.0)));
+        }
+
+        // the index follows, so the stroke is hit where it was dropped
+        c.unselect_all();
+        c.select_at(origin, p(30.0, 21.0), 4.0);
+        assert_eq!(c.selected_ids(), vec![1]);
+
+        // a new drag starts from the dropped position
+        c.finish_drag();
+        c.drag_selected(p(0.0, -20.0));
+        let Some(Shape::Freehand(f)) = c.get(1) else {
+            panic!("the stroke is still a stroke")
+        };
+        assert_eq!(f.points.first(), Some(p(10.0, 0.0)));
+        assert_eq!(
+            c.get(2).unwrap().bounds(),
+            before[1].translate(p(10.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn delete_freehand() {
+        let origin = WorldPoint::default();
+        let mut c = catalog(origin);
+
+        c.select_at(origin, p(90.0, 0.0), 4.0);
+        c.delete_selected();
+
+        assert_eq!(c.len(), 1);
+        assert_eq!(c.get(1), None);
+        assert_eq!(c.anchor(1), None);
+        assert_eq!(
+            c.visible(origin, Aabb::new(p(-10.0, -10.0), p(100.0, 100.0)))
+                .map(|(id, _, _)| id)
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn far_from_origin() {
+        // the camera has panned ten million units, and the stroke is drawn there
+        let origin = WorldPoint::new(1.0e7, -1.0e7);
+        let mut c = catalog(origin);
+
+        c.select_at(origin, p(30.0, 3.0), 4.0);
+        assert_eq!(c.selected_ids(), vec![1]);
+        c.select_at(origin, p(30.0, 5.0), 4.0);
+        assert!(!c.any_selected());
+
+        c.select_intersecting(origin, Aabb::new(p(60.25, 50.0), p(70.0, 70.0)));
+        assert_eq!(c.selected_ids(), Vec::<u32>::new());
+        c.select_intersecting(origin, Aabb::new(p(59.75, 50.0), p(70.0, 70.0)));
+        assert_eq!(c.selected_ids(), vec![2]);
+
+        let Some(Shape::Rectangle(Rectangle { position, .. })) = c.get(2) else {
+            panic!("the square is still a square")
+        };
+        assert_eq!(
+            c.anchor(2).unwrap().offset(*position),
+            WorldPoint::new(1.0e7 + 50.0, -1.0e7 + 50.0)
+        );
+    }
+
+    #[test]
+    fn text_editing() {
+        let origin = WorldPoint::default();
+        let mut c = catalog(origin);
+
+        c.upsert(3, origin, p(200.0, 0.0), |position| {
+            Shape::Text(Text::empty(position, false))
+        });
+        assert_eq!(c.shape_at(origin, p(205.0, 5.0), 4.0), Some(3));
+
+        // the box grows with the content, so the new words can be clicked
+        c.edit_text(3, "hello\nworld".to_string());
+        c.finish_text(3);
+        assert_eq!(c.shape_at(origin, p(250.0, 40.0), 4.0), Some(3));
+
+        c.select_at(origin, p(250.0, 40.0), 4.0);
+        c.drag_selected(p(0.0, 100.0));
+        c.finish_drag();
+        assert_eq!(c.shape_at(origin, p(250.0, 40.0), 4.0), None);
+        assert_eq!(c.shape_at(origin, p(250.0, 140.0), 4.0), Some(3));
+
+        // text left empty is dropped when editing ends
+        c.edit_text(3, " ".to_string());
+        c.finish_text(3);
+        assert_eq!(c.get(3), None);
+        assert_eq!(c.len(), 2);
+    }
+
+    #[test]
+    fn combinable_count() {
+        let mut c = ShapeCatalog::new();
+        rect(&mut c, 1, p(0.0, 0.0), p(40.0, 40.0));
+        c.upsert(2, WorldPoint::default(), p(0.0, 0.0), |position| {
+            Shape::from_tool(Tool::Line, position, p(40.0, 40.0), false).unwrap()
+        });
+        c.select_all();
+
+        // a line has no outline, so it cannot be combined with the square
+        assert_eq!(c.combinable_count(0.5), 1);
+        c.combine_selected(3, Operation::Union, 0.5);
+        assert_eq!(c.len(), 2);
+        assert!(c.get(3).is_none());
+
+        rect(&mut c, 3, p(20.0, 20.0), p(40.0, 40.0));
+        c.select_all();
+        assert_eq!(c.combinable_count(0.5), 2);
+        c.combine_selected(4, Operation::Union, 0.5);
+        assert!(matches!(c.get(4), Some(Shape::Compound(_))));
+        assert!(matches!(c.get(2), Some(Shape::Line(_))));
+    }
+
+    #[test]
+    fn arrow_bindings() {
+        let origin = WorldPoint::default();
+        let mut c = catalog(origin);
+
+        // a square in the next chunk over, and an arrow from the first square into it
+        let far = WorldPoint::CHUNK_SIZE as f32;
+        rect(&mut c, 3, p(far, 0.0), p(40.0, 40.0));
+        c.upsert(4, origin, p(55.0, 55.0), |position| {
+            Shape::from_tool(Tool::Arrow, position, p(far - 45.0, -45.0), false).unwrap()
+        });
+        c.bind_arrow(4);
+
+        let a = arrow(&c, 4);
+        assert_eq!(a.start_binding.map(|b| b.target), Some(2));
+        assert_eq!(a.end_binding.map(|b| b.target), Some(3));
+
+        // dragging the far square drags the arrow's end, and the start stays put
+        c.select_at(origin, p(far + 35.0, 35.0), 0.0);
+        assert_eq!(c.selected_ids(), vec![3]);
+        c.drag_selected(p(0.0, 100.0));
+        c.finish_drag();
+        let a = arrow(&c, 4);
+        assert_eq!(a.line.start(), p(55.0, 55.0));
+        assert_eq!(a.line.end(), p(far + 10.0, 110.0));
+
+        // turning it about its centre carries the anchor round
+        c.rotate_selected(std::f32::consts::PI);
+        assert!(arrow(&c, 4)
+            .line
+            .end()
+            .approx_eq(p(far + 30.0, 130.0), 1e-3));
+
+        // dragging the arrow alone off the squares unbinds it, and back on binds it again
+        let a = arrow(&c, 4);
+        c.select_at(origin, a.line.start().midpoint(a.line.end()), 0.0);
+        assert_eq!(c.selected_ids(), vec![4]);
+        c.drag_selected(p(0.0, 500.0));
+        c.finish_drag();
+        assert_eq!(arrow(&c, 4).start_binding, None);
+        assert_eq!(arrow(&c, 4).end_binding, None);
+
+        c.drag_selected(p(0.0, -500.0));
+        c.finish_drag();
+        let a = arrow(&c, 4);
+        assert!(a.start_binding.is_some() && a.end_binding.is_some());
+
+        // deleting a target unbinds the ends attached to it
+        c.select_at(origin, p(52.0, 52.0), 0.0);
+        assert_eq!(c.selected_ids(), vec![2]);
+        c.delete_selected();
+        let a = arrow(&c, 4);
+        assert_eq!(a.start_binding, None);
+        assert_eq!(a.end_binding.map(|b| b.target), Some(3));
+    }
+
+    #[test]
+    fn elbow_routing() {
+        let origin = WorldPoint::default();
+        let mut c = ShapeCatalog::new();
+        let square = |c: &mut ShapeCatalog, id: u32, at: CanvasPoint| {
+            rect(c, id, at, p(40.0, 40.0));
+        };
+        let points = |c: &ShapeCatalog| arrow(c, 3).line.points.clone();
+        let clear_of = |path: &[CanvasPoint], s: Option<&Shape>| {
+            !math::polyline::intersects_aabb(path, s.unwrap().bounds().expand(-0.01))
+        };
+
+        // an arrow between two squares side by side, bound at their centres
+        square(&mut c, 1, p(0.0, 0.0));
+        square(&mut c, 2, p(300.0, 0.0));
+        c.upsert(3, origin, p(20.0, 20.0), |position| {
+            Shape::from_tool(Tool::Arrow, position, p(300.0, 0.0), true).unwrap()
+        });
+        c.bind_arrow(3);
+        c.toggle_elbow_selected();
+        assert_eq!(points(&c), vec![p(40.0, 20.0), p(300.0, 20.0)]);
+
+        // a square dropped in between is routed round
+        square(&mut c, 4, p(150.0, 0.0));
+        let path = points(&c);
+        assert!(path.len() > 2);
+        assert!(clear_of(&path, c.get(4)));
+        assert!(path
+            .windows(2)
+            .all(|s| s[0].one() == s[1].one() || s[0].two() == s[1].two()));
+
+        // moving it away straightens the route again, live during the drag
+        c.unselect_all();
+        c.select_at(origin, p(170.0, 20.0), 0.0);
+        c.drag_selected(p(0.0, 200.0));
+        assert_eq!(points(&c), vec![p(40.0, 20.0), p(300.0, 20.0)]);
+
+        // moving a bound square reroutes from its new side
+        c.finish_drag();
+        c.select_at(origin, p(320.0, 20.0), 0.0);
+        c.drag_selected(p(-300.0, 200.0));
+        let path = points(&c);
+        assert_eq!(path.first(), Some(&p(20.0, 40.0)));
+        assert_eq!(path.last(), Some(&p(20.0, 200.0)));
+        assert!(clear_of(&path, c.get(1)) && clear_of(&path, c.get(2)));
+
+        // straightening drops the bends
+        c.finish_drag();
+        c.unselect_all();
+        c.select_at(origin, path[0].midpoint(path[1]), 0.0);
+        c.toggle_elbow_selected();
+        assert_eq!(points(&c), vec![p(20.0, 40.0), p(20.0, 200.0)]);
+    }
+
+    #[test]
+    fn elbow_routing_far_from_the_ends() {
+        let origin = WorldPoint::default();
+        let mut c = ShapeCatalog::new();
+
+        // a tall wall between the ends, with shapes far from both past either end of it
+        rect(&mut c, 1, p(90.0, -300.0), p(20.0, 600.0));
+        rect(&mut c, 2, p(60.0, -330.0), p(80.0, 20.0));
+        rect(&mut c, 3, p(60.0, 310.0), p(80.0, 20.0));
+        c.upsert(4, origin, p(0.0, 0.0), |position| {
+            Shape::from_tool(Tool::Arrow, position, p(200.0, 0.0), true).unwrap()
+        });
+        c.toggle_elbow_selected();
+
+        let a = arrow(&c, 4);
+        for id in 1..=3 {
+            let bounds = c
+                .bounds_relative_to(id, c.anchors[&4])
+                .unwrap()
+                .expand(-0.01);
+            assert!(!math::polyline::intersects_aabb(&a.line.points, bounds));
+        }
+    }
+
+    #[test]
+    fn reroute_only_nearby() {
+        let origin = WorldPoint::default();
+        let mut c = ShapeCatalog::new();
+        c.upsert(1, origin, p(0.0, 0.0), |position| {
+            Shape::from_tool(Tool::Arrow, position, p(200.0, 0.0), true).unwrap()
+        });
+        c.toggle_elbow_selected();
+
+        // a stale route shows whether the arrow was routed again
+        let stale = vec![p(0.0, 0.0), p(0.0, 50.0), p(200.0, 50.0), p(200.0, 0.0)];
+        if let Some(Shape::Arrow(a)) = c.shapes.get_mut(&1) {
+            a.line.points = stale.clone();
+        }
+
+        rect(&mut c, 2, p(3000.0, 3000.0), p(40.0, 40.0));
+        assert_eq!(arrow(&c, 1).line.points, stale);
+
+        rect(&mut c, 3, p(80.0, 40.0), p(40.0, 40.0));
+        assert_eq!(arrow(&c, 1).line.points, vec![p(0.0, 0.0), p(200.0, 0.0)]);
+    }
+}
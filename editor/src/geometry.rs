@@ -0,0 +1,31 @@
+use math::{Aabb, CanvasPoint, Transform};
+
+use crate::SvgElement;
+
+/// What every kind of shape can do, so that callers such as the app's reducer can treat
+/// them alike. Coordinates are the shape's own, which the app keeps relative to the
+/// shape's anchor.
+pub trait ShapeGeometry {
+    fn bounds(&self) -> Aabb;
+
+    /// Whether `point` lands on the shape. Thin shapes such as strokes accept points
+    /// within `tolerance`; filled shapes need the point inside.
+    fn hit_test(&self, point: CanvasPoint, tolerance: f32) -> bool;
+
+    /// Whether any part of the shape touches `rect`.
+    fn intersects_rect(&self, rect: Aabb) -> bool;
+
+    fn translate(&mut self, offset: CanvasPoint);
+
+    /// Applies `transform`. Shapes that keep a parametric form, such as rectangles and
+    /// circles, keep it, so they follow rotations and uniform scales exactly and take the
+    /// closest fit for anything else.
+    fn transform(&mut self, transform: &Transform);
+
+    /// The shape's outline as SVG path data.
+    fn to_path(&self) -> String;
+
+    /// The SVG element that draws the shape in its style. Selection is drawn separately,
+    /// on top.
+    fn render(&self) -> SvgElement;
+}
@@ -15,12 +15,19 @@ impl GuidGenerator {
     }
 }
 
+impl Default for GuidGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wasm_bindgen_test::wasm_bindgen_test;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_guid_gen() {
         let gen = GuidGenerator::new();
 
@@ -1,12 +1,36 @@
+mod catalog;
+mod geometry;
 mod guid;
+mod line;
+mod primitive;
 mod shape;
+mod spatial_index;
+mod style;
+mod svg;
 mod tool;
 
+pub use catalog::ShapeCatalog;
+pub use geometry::ShapeGeometry;
 pub use guid::GuidGenerator;
-use math::CanvasPoint;
+pub use line::{Arrow, Arrowhead, Binding, Line, Routing};
+pub use primitive::{Primitive, PrimitiveKind};
 pub use shape::*;
+pub use spatial_index::SpatialIndex;
+pub use style::{LineCap, LineJoin, Style};
+pub use svg::SvgElement;
 pub use tool::Tool;
 
-pub fn get_box(p1: CanvasPoint, p2: CanvasPoint) -> (CanvasPoint, CanvasPoint) {
-    (p1.min(p2), (p2 - p1).abs())
-}
+/// How far, in viewport pixels, a click may land from a thin shape such as a
+/// freehand stroke and still hit it.
+pub const HIT_TOLERANCE: f32 = 6.0;
+
+/// How far, in viewport pixels, a finished freehand stroke may drift when it is
+/// simplified. Strokes are drawn 4px wide, so this is not visible.
+pub const SIMPLIFY_TOLERANCE: f32 = 0.5;
+
+/// How far apart, in canvas units, the ends of a freehand stroke may be for it to
+/// count as a closed path.
+pub const CLOSE_TOLERANCE: f32 = 8.0;
+
+/// How far, in canvas units, elbow connectors keep clear of the shapes they route around.
+pub const ROUTE_MARGIN: f32 = 16.0;
@@ -0,0 +1,493 @@
+use math::{polygon, polyline, Aabb, CanvasPoint, Transform};
+
+use crate::{ShapeGeometry, Style, SvgElement};
+
+/// A straight polyline from `start` to `end`, through any points in between.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Line {
+    /// The start, any intermediate points, then the end. Always at least two.
+    pub points: Vec<CanvasPoint>,
+    pub style: Style,
+    pub selected: bool,
+}
+
+impl Line {
+    pub fn new(points: Vec<CanvasPoint>, selected: bool) -> Self {
+        assert!(points.len() >= 2, "a line needs a start and an end");
+
+        Self {
+            points,
+            style: Style::line(),
+            selected,
+        }
+    }
+
+    pub fn straight(start: CanvasPoint, end: CanvasPoint, selected: bool) -> Self {
+        Self::new(vec![start, end], selected)
+    }
+
+    pub fn start(&self) -> CanvasPoint {
+        self.points[0]
+    }
+
+    pub fn end(&self) -> CanvasPoint {
+        self.points[self.points.len() - 1]
+    }
+
+    pub fn set_start(&mut self, point: CanvasPoint) {
+        self.points[0] = point;
+    }
+
+    pub fn set_end(&mut self, point: CanvasPoint) {
+        let last = self.points.len() - 1;
+        self.points[last] = point;
+    }
+
+    /// The unit direction the line leaves its start in, pointing away from the line, or
+    /// `None` when the first segment has no length.
+    fn start_direction(&self) -> Option<CanvasPoint> {
+        direction(self.points[1], self.points[0])
+    }
+
+    /// The unit direction the line arrives at its end in, or `None` when the last segment
+    /// has no length.
+    fn end_direction(&self) -> Option<CanvasPoint> {
+        let n = self.points.len();
+
+        direction(self.points[n - 2], self.points[n - 1])
+    }
+
+    fn centreline_path(&self) -> String {
+        let points = self
+            .points
+            .iter()
+            .map(|p| {
+                let (x, y) = p.coord();
+                format!("{x} {y}")
+            })
+            .collect::<Vec<_>>();
+
+        format!("M {}", points.join(" L "))
+    }
+}
+
+fn direction(from: CanvasPoint, to: CanvasPoint) -> Option<CanvasPoint> {
+    let d = to - from;
+
+    (d.length() > f32::EPSILON).then(|| d.normalize())
+}
+
+impl ShapeGeometry for Line {
+    /// The points' bounds, widened by half the stroke width.
+    fn bounds(&self) -> Aabb {
+        Aabb::from_points(&self.points)
+            .expect("a line always has points")
+            .expand(self.style.stroke_width / 2.0)
+    }
+
+    /// Whether `point` is within `tolerance` of the stroke's edge.
+    fn hit_test(&self, point: CanvasPoint, tolerance: f32) -> bool {
+        polyline::hit_test(
+            &self.points,
+            point,
+            tolerance + self.style.stroke_width / 2.0,
+        )
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        polyline::intersects_aabb(&self.points, rect)
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.transform(&Transform::translate(offset));
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        transform.apply_in_place(&mut self.points);
+    }
+
+    fn to_path(&self) -> String {
+        self.centreline_path()
+    }
+
+    fn render(&self) -> SvgElement {
+        self.style
+            .apply(SvgElement::new("path").with("d", self.to_path()))
+            .with("fill", "none")
+    }
+}
+
+/// What is drawn at an end of an arrow.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Arrowhead {
+    #[default]
+    None,
+    /// A filled triangle with its tip at the end.
+    Triangle,
+    /// Two strokes meeting at the end.
+    Open,
+    /// A filled circle centred on the end.
+    Dot,
+    /// A stroke across the end.
+    Bar,
+}
+
+impl Arrowhead {
+    /// The length of a head for lines `stroke_width` wide.
+    pub fn size(stroke_width: f32) -> f32 {
+        6.0 + 3.0 * stroke_width
+    }
+
+    /// Whether the head is filled in the stroke colour rather than stroked.
+    fn is_filled(self) -> bool {
+        matches!(self, Arrowhead::Triangle | Arrowhead::Dot)
+    }
+
+    /// How far back from `tip` the line stops, so it does not poke through a filled tip.
+    fn inset(self, size: f32) -> f32 {
+        match self {
+            Arrowhead::Triangle => size,
+            _ => 0.0,
+        }
+    }
+
+    /// The point `size` back from `tip` along the unit `direction`, and the offset from it
+    /// to either side of the head.
+    fn frame(tip: CanvasPoint, direction: CanvasPoint, size: f32) -> (CanvasPoint, CanvasPoint) {
+        let (dx, dy) = direction.coord();
+        let back = tip - direction * CanvasPoint::splat(size);
+        let across = CanvasPoint::new(-dy, dx) * CanvasPoint::splat(size / 2.0);
+
+        (back, across)
+    }
+
+    /// Whether `point` is within `reach` of the head drawn by [`Arrowhead::path`]: inside
+    /// or near the edge of a filled head, or near the strokes of an open one.
+    pub fn hit_test(
+        self,
+        tip: CanvasPoint,
+        direction: CanvasPoint,
+        size: f32,
+        point: CanvasPoint,
+        reach: f32,
+    ) -> bool {
+        let (back, across) = Self::frame(tip, direction, size);
+
+        match self {
+            Arrowhead::None => false,
+            Arrowhead::Triangle => {
+                let corners = [tip, back + across, back - across];
+
+                polygon::contains(&corners, point)
+                    || polyline::hit_test(&[tip, back + across, back - across, tip], point, reach)
+            }
+            Arrowhead::Open => {
+                polyline::hit_test(&[back + across, tip, back - across], point, reach)
+            }
+            Arrowhead::Dot => tip.euclid_dist(point) <= size / 3.0 + reach,
+            Arrowhead::Bar => polyline::hit_test(&[tip + across, tip - across], point, reach),
+        }
+    }
+
+    /// The head at `tip` as SVG path data, where `direction` is the unit vector the line
+    /// arrives in.
+    pub fn path(self, tip: CanvasPoint, direction: CanvasPoint, size: f32) -> Option<String> {
+        let (back, across) = Self::frame(tip, direction, size);
+        let xy = |p: CanvasPoint| {
+            let (x, y) = p.coord();
+            format!("{x} {y}")
+        };
+
+        match self {
+            Arrowhead::None => None,
+            Arrowhead::Triangle => Some(format!(
+                "M {} L {} L {} Z",
+                xy(tip),
+                xy(back + across),
+                xy(back - across)
+            )),
+            Arrowhead::Open => Some(format!(
+                "M {} L {} L {}",
+                xy(back + across),
+                xy(tip),
+                xy(back - across)
+            )),
+            Arrowhead::Dot => {
+                let r = size / 3.0;
+                let (x, y) = tip.coord();
+                let (left, right) = (x - r, x + r);
+
+                Some(format!(
+                    "M {left} {y} A {r} {r} 0 1 0 {right} {y} A {r} {r} 0 1 0 {left} {y} Z"
+                ))
+            }
+            Arrowhead::Bar => Some(format!("M {} L {}", xy(tip + across), xy(tip - across))),
+        }
+    }
+}
+
+/// An arrow end attached to another shape, so it follows the shape around.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Binding {
+    /// The id of the shape the end is attached to.
+    pub target: u32,
+    /// Where on the target the end sits, in the target's own frame. See
+    /// [`Shape::anchor_at`](crate::Shape::anchor_at).
+    pub anchor: CanvasPoint,
+}
+
+/// How an arrow gets from its start to its end.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Routing {
+    /// Through its points as drawn.
+    #[default]
+    Straight,
+    /// Along horizontal and vertical segments around the shapes in the way, rerouted
+    /// whenever they move.
+    Elbow,
+}
+
+/// A line with a head at either end. By default it points from its start to its end.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Arrow {
+    pub line: Line,
+    pub start_head: Arrowhead,
+    pub end_head: Arrowhead,
+    pub start_binding: Option<Binding>,
+    pub end_binding: Option<Binding>,
+    pub routing: Routing,
+}
+
+impl Arrow {
+    pub fn new(points: Vec<CanvasPoint>, selected: bool) -> Self {
+        Self {
+            line: Line::new(points, selected),
+            start_head: Arrowhead::None,
+            end_head: Arrowhead::Triangle,
+            start_binding: None,
+            end_binding: None,
+            routing: Routing::Straight,
+        }
+    }
+
+    pub fn straight(start: CanvasPoint, end: CanvasPoint, selected: bool) -> Self {
+        Self::new(vec![start, end], selected)
+    }
+
+    /// Detaches any end attached to `target`.
+    pub fn unbind(&mut self, target: u32) {
+        for binding in [&mut self.start_binding, &mut self.end_binding] {
+            if binding.is_some_and(|b| b.target == target) {
+                *binding = None;
+            }
+        }
+    }
+
+    fn head_size(&self) -> f32 {
+        Arrowhead::size(self.line.style.stroke_width)
+    }
+
+    /// Each head with its path, for the ends long enough to point somewhere.
+    fn heads(&self) -> impl Iterator<Item = (Arrowhead, String)> + '_ {
+        let size = self.head_size();
+        let start = self.line.start_direction().and_then(|d| {
+            Some((
+                self.start_head,
+                self.start_head.path(self.line.start(), d, size)?,
+            ))
+        });
+        let end = self
+            .line
+            .end_direction()
+            .and_then(|d| Some((self.end_head, self.end_head.path(self.line.end(), d, size)?)));
+
+        start.into_iter().chain(end)
+    }
+
+    /// The line as drawn, pulled back from filled tips.
+    fn shaft(&self) -> Line {
+        let size = self.head_size();
+        let points = &self.line.points;
+        let n = points.len();
+        let first = points[0].euclid_dist(points[1]);
+        let last = points[n - 2].euclid_dist(points[n - 1]);
+
+        // never pull back past the other end of the segment, which both heads share when
+        // there is only one
+        let mut start_inset = self.start_head.inset(size).min(first);
+        let mut end_inset = self.end_head.inset(size).min(last);
+        if n == 2 && start_inset + end_inset > first {
+            let scale = first / (start_inset + end_inset);
+            start_inset *= scale;
+            end_inset *= scale;
+        }
+
+        let mut shaft = self.line.clone();
+        if let Some(d) = self.line.start_direction() {
+            shaft.set_start(self.line.start() - d * CanvasPoint::splat(start_inset));
+        }
+        if let Some(d) = self.line.end_direction() {
+            shaft.set_end(self.line.end() - d * CanvasPoint::splat(end_inset));
+        }
+
+        shaft
+    }
+
+    fn has_heads(&self) -> bool {
+        self.start_head != Arrowhead::None || self.end_head != Arrowhead::None
+    }
+}
+
+impl ShapeGeometry for Arrow {
+    /// The line's bounds, widened to take in the heads.
+    fn bounds(&self) -> Aabb {
+        let bounds = self.line.bounds();
+
+        if self.has_heads() {
+            bounds.expand(self.head_size())
+        } else {
+            bounds
+        }
+    }
+
+    /// Whether `point` is within `tolerance` of the line or of either head as drawn.
+    fn hit_test(&self, point: CanvasPoint, tolerance: f32) -> bool {
+        let (size, reach) = (
+            self.head_size(),
+            tolerance + self.line.style.stroke_width / 2.0,
+        );
+        let on_head = |head: Arrowhead, tip: CanvasPoint, direction: Option<CanvasPoint>| {
+            direction.is_some_and(|d| head.hit_test(tip, d, size, point, reach))
+        };
+
+        self.line.hit_test(point, tolerance)
+            || on_head(
+                self.start_head,
+                self.line.start(),
+                self.line.start_direction(),
+            )
+            || on_head(self.end_head, self.line.end(), self.line.end_direction())
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        self.line.intersects_rect(rect)
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.line.translate(offset);
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        self.line.transform(transform);
+    }
+
+    fn to_path(&self) -> String {
+        self.heads()
+            .fold(self.shaft().to_path(), |path, (_, head)| path + " " + &head)
+    }
+
+    /// A group of the shaft and the heads, with filled heads painted in the stroke colour.
+    fn render(&self) -> SvgElement {
+        let ink = self.line.style.stroke.as_deref().unwrap_or("none");
+
+        self.heads().fold(
+            self.line
+                .style
+                .apply(SvgElement::new("g"))
+                .with("fill", "none")
+                .with_child(SvgElement::new("path").with("d", self.shaft().to_path())),
+            |group, (head, path)| {
+                let fill = if head.is_filled() { ink } else { "none" };
+
+                group.with_child(SvgElement::new("path").with("d", path).with("fill", fill))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn p(x: f32, y: f32) -> CanvasPoint {
+        CanvasPoint::new(x, y)
+    }
+
+    #[test]
+    fn line_hit_test() {
+        // an elbow through (100, 0), drawn 2 wide
+        let line = Line::new(vec![p(0.0, 0.0), p(100.0, 0.0), p(100.0, 100.0)], false);
+
+        assert!(line.hit_test(p(50.0, 4.0), 3.0));
+        assert!(!line.hit_test(p(50.0, 5.0), 3.0));
+        assert!(line.hit_test(p(103.0, 50.0), 3.0));
+        assert!(!line.hit_test(p(50.0, 50.0), 3.0));
+
+        assert!(line.intersects_rect(Aabb::new(p(90.0, 40.0), p(110.0, 60.0))));
+        assert!(!line.intersects_rect(Aabb::new(p(10.0, 10.0), p(90.0, 90.0))));
+        assert_eq!(line.to_path(), "M 0 0 L 100 0 L 100 100");
+    }
+
+    #[test]
+    fn arrowheads() {
+        let mut arrow = Arrow::straight(p(0.0, 0.0), p(100.0, 0.0), false);
+        let size = Arrowhead::size(arrow.line.style.stroke_width);
+
+        // the shaft stops at the base of the triangle, which is filled in the ink
+        let element = arrow.render();
+        assert_eq!(element.tag, "g");
+        assert_eq!(element.children.len(), 2);
+        assert_eq!(
+            element.children[0].attribute("d"),
+            Some(format!("M 0 0 L {} 0", 100.0 - size).as_str())
+        );
+        assert_eq!(element.children[1].attribute("fill"), Some("#000000"));
+        // the triangle is hit inside, but not past its tip or beside its point
+        assert!(arrow.hit_test(p(100.0 - size * 0.75, size * 0.3), 0.0));
+        assert!(!arrow.hit_test(p(100.0 + size, 0.0), 0.0));
+        assert!(!arrow.hit_test(p(100.0 - size * 0.25, size * 0.4), 0.0));
+        assert!(!arrow.hit_test(p(0.0 - size, 0.0), 0.0));
+
+        arrow.start_head = Arrowhead::Bar;
+        arrow.end_head = Arrowhead::Open;
+        let element = arrow.render();
+        assert_eq!(element.children[0].attribute("d"), Some("M 0 0 L 100 0"));
+        assert_eq!(
+            element.children[1].attribute("d"),
+            Some(format!("M 0 {} L 0 {}", -size / 2.0, size / 2.0).as_str())
+        );
+        assert_eq!(element.children[2].attribute("fill"), Some("none"));
+        // the bar is hit along its length, and the open head only on its strokes
+        assert!(arrow.hit_test(p(0.0, size * 0.4), 0.0));
+        assert!(!arrow.hit_test(p(0.0 - size, 0.0), 0.0));
+        assert!(arrow.hit_test(p(100.0 - size / 2.0, size / 4.0), 0.0));
+        assert!(!arrow.hit_test(p(100.0 - size * 0.75, size * 0.15), 0.0));
+
+        // the shaft of an arrow shorter than its heads stops short rather than turning back
+        let mut short = Arrow::straight(p(0.0, 0.0), p(5.0, 0.0), false);
+        let shaft = |a: &Arrow| a.render().children[0].attribute("d").map(str::to_string);
+        assert_eq!(shaft(&short).as_deref(), Some("M 0 0 L 0 0"));
+        short.line.points = vec![p(0.0, 0.0), p(10.0, 0.0)];
+        short.start_head = Arrowhead::Triangle;
+        assert_eq!(shaft(&short).as_deref(), Some("M 5 0 L 5 0"));
+
+        // a line with no length has nowhere to point
+        let dot = Arrow::straight(p(5.0, 5.0), p(5.0, 5.0), false);
+        assert_eq!(dot.render().children.len(), 1);
+    }
+
+    #[test]
+    fn arrow_transform() {
+        let mut arrow = Arrow::new(vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0)], false);
+        arrow.translate(p(5.0, 5.0));
+
+        assert_eq!(
+            arrow.line.points,
+            vec![p(5.0, 5.0), p(15.0, 5.0), p(15.0, 15.0)]
+        );
+        assert!(arrow.bounds().contains(p(15.0, 15.0 + arrow.head_size())));
+    }
+}
@@ -0,0 +1,360 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use math::{polygon, Aabb, CanvasPoint, Ellipse, Obb, Transform};
+
+use crate::{ShapeGeometry, Style, SvgElement};
+
+/// How closely, in canvas units, a cylinder's curved caps are followed when hit testing.
+const HIT_FLATTENING: f32 = 0.1;
+
+/// The outline of a [`Primitive`], stretched to fill its box.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PrimitiveKind {
+    /// A regular polygon with `sides` corners, at least three. Odd ones point up and even
+    /// ones have a corner at either side, so four sides make a diamond and six the
+    /// flowchart hexagon.
+    Polygon { sides: u32 },
+    /// A star with `points` outer corners, at least three, pointing up. The inner corners
+    /// sit at `inner_ratio` of the way out to the outer ones.
+    Star { points: u32, inner_ratio: f32 },
+    /// A box whose top edge sits `skew` of the width to the right of its bottom edge,
+    /// from -1 to 1. Negative skews lean the other way.
+    Parallelogram { skew: f32 },
+    /// An upright cylinder, the flowchart symbol for stored data, with elliptical caps
+    /// [`PrimitiveKind::CYLINDER_CAP`] of its height deep.
+    Cylinder,
+}
+
+impl PrimitiveKind {
+    pub const DIAMOND: Self = Self::Polygon { sides: 4 };
+    pub const TRIANGLE: Self = Self::Polygon { sides: 3 };
+    pub const HEXAGON: Self = Self::Polygon { sides: 6 };
+    pub const STAR: Self = Self::Star {
+        points: 5,
+        inner_ratio: 0.5,
+    };
+    pub const PARALLELOGRAM: Self = Self::Parallelogram { skew: 0.25 };
+
+    /// How deep each of a cylinder's caps is, as a fraction of its height.
+    pub const CYLINDER_CAP: f32 = 0.2;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Polygon { sides: 3 } => "Triangle",
+            Self::Polygon { sides: 4 } => "Diamond",
+            Self::Polygon { sides: 6 } => "Hexagon",
+            Self::Polygon { .. } => "Polygon",
+            Self::Star { .. } => "Star",
+            Self::Parallelogram { .. } => "Parallelogram",
+            Self::Cylinder => "Cylinder",
+        }
+    }
+
+    /// The corners in the box's own frame, where it spans `-1..=1` on each axis, going
+    /// clockwise on screen. Cylinders are curved and have none.
+    pub fn corners(self) -> Vec<CanvasPoint> {
+        let around = |n: u32, start: f32, radius: &dyn Fn(u32) -> f32| {
+            fit((0..n)
+                .map(|i| {
+                    let (sin, cos) = (start + i as f32 / n as f32 * TAU).sin_cos();
+                    CanvasPoint::new(cos, sin) * CanvasPoint::splat(radius(i))
+                })
+                .collect())
+        };
+
+        match self {
+            Self::Polygon { sides } => {
+                let n = sides.max(3);
+                let start = if n % 2 == 0 { 0.0 } else { -FRAC_PI_2 };
+
+                around(n, start, &|_| 1.0)
+            }
+            Self::Star {
+                points,
+                inner_ratio,
+            } => around(points.max(3) * 2, -FRAC_PI_2, &|i| {
+                if i % 2 == 0 {
+                    1.0
+                } else {
+                    inner_ratio
+                }
+            }),
+            Self::Parallelogram { skew } => {
+                let shift = 2.0 * skew.clamp(-1.0, 1.0);
+                let (right, left) = (shift.max(0.0), shift.min(0.0));
+
+                vec![
+                    CanvasPoint::new(-1.0 + right, -1.0),
+                    CanvasPoint::new(1.0 + left, -1.0),
+                    CanvasPoint::new(1.0 - right, 1.0),
+                    CanvasPoint::new(-1.0 - left, 1.0),
+                ]
+            }
+            Self::Cylinder => Vec::new(),
+        }
+    }
+}
+
+/// Stretches `points` so their bounds span `-1..=1` on each axis.
+fn fit(points: Vec<CanvasPoint>) -> Vec<CanvasPoint> {
+    let Some(bounds) = Aabb::from_points(&points) else {
+        return points;
+    };
+    let scale = CanvasPoint::splat(2.0) / bounds.width_height();
+
+    points
+        .into_iter()
+        .map(|p| (p - bounds.min) * scale - CanvasPoint::splat(1.0))
+        .collect()
+}
+
+/// A diagram shape such as a diamond, star or cylinder, drawn by dragging out its box
+/// like a rectangle and stretched to fill it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Primitive {
+    pub kind: PrimitiveKind,
+    pub position: CanvasPoint,
+    pub width_height: CanvasPoint,
+    /// Radians about the centre, clockwise on screen.
+    pub rotation: f32,
+    pub style: Style,
+    pub selected: bool,
+}
+
+impl Primitive {
+    /// The `kind` of shape filling the box from `position` by `width_height`, which may
+    /// be negative.
+    pub fn new(
+        kind: PrimitiveKind,
+        position: CanvasPoint,
+        width_height: CanvasPoint,
+        selected: bool,
+    ) -> Self {
+        let aabb = Aabb::from_position_size(position, width_height);
+
+        Self {
+            kind,
+            position: aabb.min,
+            width_height: aabb.width_height(),
+            rotation: 0.0,
+            style: Style::default(),
+            selected,
+        }
+    }
+
+    pub fn obb(&self) -> Obb {
+        Obb::from_aabb(
+            Aabb::from_position_size(self.position, self.width_height),
+            self.rotation,
+        )
+    }
+
+    /// The top and bottom caps of a cylinder filling the box.
+    fn caps(&self) -> [Ellipse; 2] {
+        let obb = self.obb();
+        let [_, v] = obb.axes();
+        let (hx, hy) = obb.half_extents.coord();
+        let depth = PrimitiveKind::CYLINDER_CAP * hy;
+        let offset = v * CanvasPoint::splat(hy - depth);
+        let radii = CanvasPoint::new(hx, depth);
+
+        [
+            Ellipse::new(obb.center - offset, radii, self.rotation),
+            Ellipse::new(obb.center + offset, radii, self.rotation),
+        ]
+    }
+
+    /// The outline as a polygon, with a cylinder's caps followed within `tolerance`.
+    pub fn polygon(&self, tolerance: f32) -> Vec<CanvasPoint> {
+        if self.kind == PrimitiveKind::Cylinder {
+            let [top, bottom] = self.caps();
+
+            // over the top of one cap and round the bottom of the other
+            let mut points = top.arc(PI, PI, tolerance);
+            points.extend(bottom.arc(0.0, PI, tolerance));
+            return points;
+        }
+
+        let obb = self.obb();
+        let frame = Transform::scale(obb.half_extents).then(obb.transform());
+
+        frame.apply_all(&self.kind.corners())
+    }
+}
+
+impl ShapeGeometry for Primitive {
+    fn bounds(&self) -> Aabb {
+        self.obb().bounds()
+    }
+
+    fn hit_test(&self, point: CanvasPoint, _tolerance: f32) -> bool {
+        self.obb().contains(point) && polygon::contains(&self.polygon(HIT_FLATTENING), point)
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        let outline = self.polygon(HIT_FLATTENING);
+
+        self.obb().intersects_aabb(rect)
+            && (polygon::contains(&outline, rect.center())
+                || polygon::edges(&outline).any(|e| e.intersects_aabb(rect)))
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.position = self.position + offset;
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        let obb = self.obb().transformed(transform);
+
+        self.rotation = obb.rotation;
+        self.position = obb.center - obb.half_extents;
+        self.width_height = obb.half_extents * CanvasPoint::splat(2.0);
+    }
+
+    /// Straight-sided outlines as a closed path. Cylinders are drawn with exact arcs, and
+    /// a second path for the near rim of the top cap.
+    fn to_path(&self) -> String {
+        if self.kind == PrimitiveKind::Cylinder {
+            let [top, bottom] = self.caps();
+            let [u, _] = top.axes();
+            let side = u * CanvasPoint::splat(top.radii.one());
+            let (rx, ry) = top.radii.coord();
+            let degrees = self.rotation.to_degrees();
+            let (x1, y1) = (top.center - side).coord();
+            let (x2, y2) = (top.center + side).coord();
+            let (x3, y3) = (bottom.center + side).coord();
+            let (x4, y4) = (bottom.center - side).coord();
+            let arc = format!("A {rx} {ry} {degrees} 0 1");
+
+            // the rim runs clockwise too, so it stays filled under the nonzero rule
+            return format!(
+                "M {x1} {y1} {arc} {x2} {y2} L {x3} {y3} {arc} {x4} {y4} Z M {x2} {y2} {arc} {x1} {y1}"
+            );
+        }
+
+        let points = self
+            .polygon(HIT_FLATTENING)
+            .iter()
+            .map(|p| {
+                let (x, y) = p.coord();
+                format!("{x} {y}")
+            })
+            .collect::<Vec<_>>();
+
+        format!("M {} Z", points.join(" L "))
+    }
+
+    fn render(&self) -> SvgElement {
+        self.style.apply(
+            SvgElement::new("path")
+                .with("d", self.to_path())
+                .with("fill-rule", "nonzero"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn p(x: f32, y: f32) -> CanvasPoint {
+        CanvasPoint::new(x, y)
+    }
+
+    fn assert_corners(kind: PrimitiveKind, expected: &[CanvasPoint]) {
+        let corners = kind.corners();
+
+        assert_eq!(corners.len(), expected.len(), "{kind:?}");
+        for (corner, &e) in corners.iter().zip(expected) {
+            assert!(corner.approx_eq(e, 1e-5), "{kind:?}: {corner:?} != {e:?}");
+        }
+    }
+
+    #[test]
+    fn corners() {
+        let diamond = [p(1.0, 0.0), p(0.0, 1.0), p(-1.0, 0.0), p(0.0, -1.0)];
+        assert_corners(PrimitiveKind::DIAMOND, &diamond);
+
+        // the triangle is stretched down to fill the box
+        let triangle = [p(0.0, -1.0), p(1.0, 1.0), p(-1.0, 1.0)];
+        assert_corners(PrimitiveKind::TRIANGLE, &triangle);
+
+        // flat on top and bottom
+        let hexagon = PrimitiveKind::HEXAGON.corners();
+        let level = |y: f32| {
+            hexagon
+                .iter()
+                .filter(|c| (c.two() - y).abs() < 1e-5)
+                .count()
+        };
+        assert_eq!((level(-1.0), level(1.0)), (2, 2));
+
+        let star = PrimitiveKind::STAR.corners();
+        assert_eq!(star.len(), 10);
+        assert!(star[0].approx_eq(p(0.0, -1.0), 1e-5));
+        assert!(star[1].length() < 0.7);
+
+        let leaning = [p(-0.5, -1.0), p(1.0, -1.0), p(0.5, 1.0), p(-1.0, 1.0)];
+        assert_corners(PrimitiveKind::PARALLELOGRAM, &leaning);
+        let back = [p(-1.0, -1.0), p(0.5, -1.0), p(1.0, 1.0), p(-0.5, 1.0)];
+        assert_corners(PrimitiveKind::Parallelogram { skew: -0.25 }, &back);
+
+        // too few sides still make a triangle
+        assert_eq!(PrimitiveKind::Polygon { sides: 1 }.corners().len(), 3);
+        assert!(PrimitiveKind::Cylinder.corners().is_empty());
+    }
+
+    #[test]
+    fn hit_test() {
+        let diamond = Primitive::new(
+            PrimitiveKind::DIAMOND,
+            p(40.0, 40.0),
+            p(-40.0, -40.0),
+            false,
+        );
+        assert_eq!(diamond.bounds(), Aabb::new(p(0.0, 0.0), p(40.0, 40.0)));
+        assert!(diamond.hit_test(p(20.0, 2.0), 0.0));
+        assert!(!diamond.hit_test(p(5.0, 5.0), 0.0));
+
+        // the corner box is inside the bounds but misses the diamond
+        assert!(!diamond.intersects_rect(Aabb::new(p(0.0, 0.0), p(8.0, 8.0))));
+        assert!(diamond.intersects_rect(Aabb::new(p(0.0, 0.0), p(12.0, 12.0))));
+        assert!(diamond.intersects_rect(Aabb::new(p(18.0, 18.0), p(22.0, 22.0))));
+
+        // between two points of the star
+        let star = Primitive::new(PrimitiveKind::STAR, p(0.0, 0.0), p(100.0, 100.0), false);
+        assert!(star.hit_test(p(50.0, 50.0), 0.0));
+        assert!(!star.hit_test(p(70.0, 15.0), 0.0));
+    }
+
+    #[test]
+    fn cylinder() {
+        let c = Primitive::new(PrimitiveKind::Cylinder, p(0.0, 0.0), p(40.0, 100.0), false);
+
+        // caps 20 deep at either end
+        assert!(c.hit_test(p(20.0, 1.0), 0.0));
+        assert!(c.hit_test(p(1.0, 20.0), 0.0));
+        assert!(!c.hit_test(p(2.0, 2.0), 0.0));
+        assert!(!c.hit_test(p(38.0, 98.0), 0.0));
+
+        let path = c.to_path();
+        assert_eq!(path.matches('A').count(), 3);
+        assert!(path.starts_with("M 0 10 A 20 10 0 0 1 40 10 L 40 90"));
+    }
+
+    #[test]
+    fn transform() {
+        let mut s = Primitive::new(PrimitiveKind::TRIANGLE, p(0.0, 0.0), p(20.0, 10.0), false);
+        s.transform(&Transform::rotate(FRAC_PI_2));
+
+        // the apex, once at the top, now points right
+        assert_eq!(s.kind, PrimitiveKind::TRIANGLE);
+        assert!(s.width_height.approx_eq(p(20.0, 10.0), 1e-5));
+        assert!(s.polygon(0.1)[0].approx_eq(p(0.0, 10.0), 1e-4));
+        assert!(s.hit_test(p(-8.0, 15.0), 0.0));
+        assert!(!s.hit_test(p(-2.0, 15.0), 0.0));
+    }
+}
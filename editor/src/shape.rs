@@ -1,18 +1,273 @@
-use math::CanvasPoint;
+use math::bezier::{self, Bezier, CubicBezier};
+use math::boolean::{self, Operation};
+use math::stroke::{self, StrokeOptions, StrokePoint, DEFAULT_PRESSURE};
+use math::{polygon, simplify, Aabb, CanvasPoint, Obb, PointBuffer, Transform};
+
+use crate::{Arrow, Line, Primitive, ShapeGeometry, Style, SvgElement, Tool, CLOSE_TOLERANCE};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Shape {
     Rectangle(Rectangle),
     Circle(Circle),
+    Ellipse(Ellipse),
+    Primitive(Primitive),
     Freehand(Freehand),
+    Compound(Compound),
+    Text(Text),
+    Line(Line),
+    Arrow(Arrow),
+}
+
+impl Shape {
+    /// The shape `tool` draws when dragged out from `position` by `width_height`: a box
+    /// for rectangles, the ellipses inscribed in them and diagram shapes, a radius for
+    /// circles, and the vector to the end for lines and arrows. Tools that do not drag out
+    /// shapes give `None`.
+    pub fn from_tool(
+        tool: Tool,
+        position: CanvasPoint,
+        width_height: CanvasPoint,
+        selected: bool,
+    ) -> Option<Self> {
+        match tool {
+            Tool::Rect => Some(Shape::Rectangle(Rectangle::new(
+                position,
+                width_height,
+                selected,
+            ))),
+            Tool::Circle => Some(Shape::Circle(Circle::new(
+                position,
+                width_height.length(),
+                selected,
+            ))),
+            Tool::Ellipse => Some(Shape::Ellipse(Ellipse::from_box(
+                position,
+                width_height,
+                selected,
+            ))),
+            Tool::Primitive(kind) => Some(Shape::Primitive(Primitive::new(
+                kind,
+                position,
+                width_height,
+                selected,
+            ))),
+            Tool::Line => Some(Shape::Line(Line::straight(
+                position,
+                position + width_height,
+                selected,
+            ))),
+            Tool::Arrow => Some(Shape::Arrow(Arrow::straight(
+                position,
+                position + width_height,
+                selected,
+            ))),
+            _ => None,
+        }
+    }
+
+    fn geometry(&self) -> &dyn ShapeGeometry {
+        match self {
+            Shape::Rectangle(r) => r,
+            Shape::Primitive(p) => p,
+            Shape::Circle(c) => c,
+            Shape::Ellipse(e) => e,
+            Shape::Freehand(f) => f,
+            Shape::Compound(c) => c,
+            Shape::Text(t) => t,
+            Shape::Line(l) => l,
+            Shape::Arrow(a) => a,
+        }
+    }
+
+    fn geometry_mut(&mut self) -> &mut dyn ShapeGeometry {
+        match self {
+            Shape::Rectangle(r) => r,
+            Shape::Primitive(p) => p,
+            Shape::Circle(c) => c,
+            Shape::Ellipse(e) => e,
+            Shape::Freehand(f) => f,
+            Shape::Compound(c) => c,
+            Shape::Text(t) => t,
+            Shape::Line(l) => l,
+            Shape::Arrow(a) => a,
+        }
+    }
+
+    pub fn is_selected(&self) -> bool {
+        match self {
+            Shape::Rectangle(r) => r.selected,
+            Shape::Primitive(p) => p.selected,
+            Shape::Circle(c) => c.selected,
+            Shape::Ellipse(e) => e.selected,
+            Shape::Freehand(f) => f.selected,
+            Shape::Compound(c) => c.selected,
+            Shape::Text(t) => t.selected,
+            Shape::Line(l) => l.selected,
+            Shape::Arrow(a) => a.line.selected,
+        }
+    }
+
+    pub fn set_selected(&mut self, selected: bool) {
+        match self {
+            Shape::Rectangle(r) => r.selected = selected,
+            Shape::Primitive(p) => p.selected = selected,
+            Shape::Circle(c) => c.selected = selected,
+            Shape::Ellipse(e) => e.selected = selected,
+            Shape::Freehand(f) => f.selected = selected,
+            Shape::Compound(c) => c.selected = selected,
+            Shape::Text(t) => t.selected = selected,
+            Shape::Line(l) => l.selected = selected,
+            Shape::Arrow(a) => a.line.selected = selected,
+        }
+    }
+
+    pub fn style(&self) -> &Style {
+        match self {
+            Shape::Rectangle(r) => &r.style,
+            Shape::Primitive(p) => &p.style,
+            Shape::Circle(c) => &c.style,
+            Shape::Ellipse(e) => &e.style,
+            Shape::Freehand(f) => &f.style,
+            Shape::Compound(c) => &c.style,
+            Shape::Text(t) => &t.style,
+            Shape::Line(l) => &l.style,
+            Shape::Arrow(a) => &a.line.style,
+        }
+    }
+
+    pub fn style_mut(&mut self) -> &mut Style {
+        match self {
+            Shape::Rectangle(r) => &mut r.style,
+            Shape::Primitive(p) => &mut p.style,
+            Shape::Circle(c) => &mut c.style,
+            Shape::Ellipse(e) => &mut e.style,
+            Shape::Freehand(f) => &mut f.style,
+            Shape::Compound(c) => &mut c.style,
+            Shape::Text(t) => &mut t.style,
+            Shape::Line(l) => &mut l.style,
+            Shape::Arrow(a) => &mut a.line.style,
+        }
+    }
+
+    /// Where `point` sits in the shape's own frame, for arrows to bind to: `-1..=1` across
+    /// each axis of a rectangle, diagram shape or ellipse, turning with it, or of a
+    /// circle's bounding square. Only these can be bound to.
+    pub fn anchor_at(&self, point: CanvasPoint) -> Option<CanvasPoint> {
+        let unit = |v: f32, half: f32| if half > 0.0 { v / half } else { 0.0 };
+        let in_box = |obb: Obb| {
+            let (x, y) = obb.to_local(point).coord();
+            let (hx, hy) = obb.half_extents.coord();
+
+            Some(CanvasPoint::new(unit(x, hx), unit(y, hy)))
+        };
+
+        match self {
+            Shape::Rectangle(r) => in_box(r.obb()),
+            Shape::Primitive(p) => in_box(p.obb()),
+            Shape::Circle(c) => {
+                let (x, y) = (point - c.center).coord();
+
+                Some(CanvasPoint::new(unit(x, c.radius), unit(y, c.radius)))
+            }
+            Shape::Ellipse(e) => Some(e.ellipse().to_unit(point)),
+            _ => None,
+        }
+    }
+
+    /// The point at `anchor` in the shape's own frame, the inverse of
+    /// [`Shape::anchor_at`].
+    pub fn anchor_point(&self, anchor: CanvasPoint) -> Option<CanvasPoint> {
+        let in_box = |obb: Obb| Some(obb.transform().apply(anchor * obb.half_extents));
+
+        match self {
+            Shape::Rectangle(r) => in_box(r.obb()),
+            Shape::Primitive(p) => in_box(p.obb()),
+            Shape::Circle(c) => Some(c.center + anchor * CanvasPoint::splat(c.radius)),
+            Shape::Ellipse(e) => Some(e.ellipse().transform().apply(anchor)),
+            _ => None,
+        }
+    }
+
+    /// Turns the shape by `radians` about its own centre, clockwise on screen.
+    pub fn rotate(&mut self, radians: f32) {
+        let center = self.bounds().center();
+        let about_center = Transform::translate(-center)
+            .then(Transform::rotate(radians))
+            .then(Transform::translate(center));
+
+        self.transform(&about_center);
+    }
+
+    /// The filled region as closed contours, with curves flattened to within `tolerance`.
+    /// Open freehand strokes, text, lines and arrows have none.
+    pub fn outline(&self, tolerance: f32) -> Option<Vec<Vec<CanvasPoint>>> {
+        match self {
+            Shape::Rectangle(r) => Some(vec![r.polygon()]),
+            Shape::Circle(c) => Some(vec![c.polygon(tolerance)]),
+            Shape::Ellipse(e) => Some(vec![e.polygon(tolerance)]),
+            Shape::Primitive(p) => Some(vec![p.polygon(tolerance)]),
+            Shape::Freehand(f) => f.is_closed().then(|| vec![f.polygon(tolerance)]),
+            Shape::Compound(c) => Some(c.contours.clone()),
+            Shape::Text(_) | Shape::Line(_) | Shape::Arrow(_) => None,
+        }
+    }
+}
+
+impl ShapeGeometry for Shape {
+    fn bounds(&self) -> Aabb {
+        self.geometry().bounds()
+    }
+
+    fn hit_test(&self, point: CanvasPoint, tolerance: f32) -> bool {
+        self.geometry().hit_test(point, tolerance)
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        self.geometry().intersects_rect(rect)
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.geometry_mut().translate(offset)
+    }
+
+    /// Circles that are stretched or skewed become ellipses, so they follow the transform
+    /// exactly.
+    fn transform(&mut self, transform: &Transform) {
+        if let Shape::Circle(c) = self {
+            let e = c.ellipse().transformed(transform);
+
+            if !e.radii.relative_eq(CanvasPoint::splat(e.radii.one()), 1e-5) {
+                *self = Shape::Ellipse(Ellipse {
+                    center: e.center,
+                    radii: e.radii,
+                    rotation: e.rotation,
+                    style: c.style.clone(),
+                    selected: c.selected,
+                });
+                return;
+            }
+        }
+
+        self.geometry_mut().transform(transform)
+    }
+
+    fn to_path(&self) -> String {
+        self.geometry().to_path()
+    }
+
+    fn render(&self) -> SvgElement {
+        self.geometry().render()
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Rectangle {
     pub position: CanvasPoint,
     pub width_height: CanvasPoint,
+    /// Radians about the centre, clockwise on screen.
+    pub rotation: f32,
+    pub style: Style,
     pub selected: bool,
-    pub temp_position: Option<CanvasPoint>,
 }
 
 impl Rectangle {
@@ -20,43 +275,277 @@ impl Rectangle {
         Self {
             position: p1,
             width_height: p2,
+            rotation: 0.0,
+            style: Style::default(),
             selected,
-            temp_position: None,
         }
     }
 
-    pub fn is_inside(&self, global_pointer: CanvasPoint) -> bool {
-        global_pointer >= self.position && global_pointer <= self.position + self.width_height
+    pub fn obb(&self) -> Obb {
+        Obb::from_aabb(
+            Aabb::from_position_size(self.position, self.width_height),
+            self.rotation,
+        )
     }
 
-    pub fn intersects(&self, selection_box: (CanvasPoint, CanvasPoint)) -> bool {
-        !((self.position + self.width_height).le_or(selection_box.0)
-            || (selection_box.0 + selection_box.1).le_or(self.position))
+    pub fn polygon(&self) -> Vec<CanvasPoint> {
+        self.obb().corners().to_vec()
     }
+}
 
-    pub fn path(&self) -> String {
-        let (x, y) = self.position.coord();
-        let (w, h) = self.width_height.coord();
-        format!("M {x} {y} h {w} v {h} h -{w} Z")
+impl ShapeGeometry for Rectangle {
+    fn bounds(&self) -> Aabb {
+        self.obb().bounds()
+    }
+
+    fn hit_test(&self, point: CanvasPoint, _tolerance: f32) -> bool {
+        self.obb().contains(point)
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        self.obb().intersects_aabb(rect)
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.position = self.position + offset;
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        let obb = self.obb().transformed(transform);
+
+        self.rotation = obb.rotation;
+        self.position = obb.center - obb.half_extents;
+        self.width_height = obb.half_extents * CanvasPoint::splat(2.0);
+    }
+
+    fn to_path(&self) -> String {
+        let [(x1, y1), (x2, y2), (x3, y3), (x4, y4)] = self.obb().corners().map(|p| p.coord());
+        format!("M {x1} {y1} L {x2} {y2} L {x3} {y3} L {x4} {y4} Z")
+    }
+
+    fn render(&self) -> SvgElement {
+        self.style
+            .apply(SvgElement::new("path").with("d", self.to_path()))
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Freehand {
-    pub points: Vec<CanvasPoint>,
+    /// The centreline used for hit testing: every sample while drawing, simplified once
+    /// the stroke is finished.
+    pub points: PointBuffer,
+    /// Every pointer sample as drawn, with its pressure and time.
+    pub samples: Vec<StrokePoint>,
+    /// The smooth spline fitted to the stroke once it is finished, which the ink follows.
+    /// Empty while drawing.
+    pub curve: Vec<CubicBezier>,
+    /// The filled ink outline once the stroke is finished. Empty while drawing.
+    pub outline: Vec<CanvasPoint>,
+    pub style: Style,
     pub selected: bool,
 }
 
 impl Freehand {
+    /// A stroke starting at `point` with the pressure a mouse reports.
     pub fn new(point: CanvasPoint, selected: bool) -> Self {
+        Self::from_sample(StrokePoint::new(point, DEFAULT_PRESSURE, 0.0), selected)
+    }
+
+    pub fn from_sample(sample: StrokePoint, selected: bool) -> Self {
         Self {
-            points: vec![point],
+            points: PointBuffer::from(vec![sample.point]),
+            samples: vec![sample],
+            curve: Vec::new(),
+            outline: Vec::new(),
+            style: Style::ink(),
             selected,
         }
     }
 
-    pub fn intersects(&self, selection_box: (CanvasPoint, CanvasPoint)) -> bool {
-        false
+    pub fn push(&mut self, sample: StrokePoint) {
+        self.points.push(sample.point);
+        self.samples.push(sample);
+    }
+
+    /// Fits the raw stroke to a cubic spline and builds its ink outline along it, then
+    /// drops points that lie within `tolerance` of the rest of the stroke for hit testing.
+    /// Call this once the stroke is finished; all stay within `tolerance` of what was drawn.
+    pub fn finish(&mut self, tolerance: f32) {
+        let points = self.points.to_vec();
+        self.curve = bezier::fit_cubic(&points, tolerance);
+
+        let outline = stroke::outline(&self.curve_samples(tolerance), &StrokeOptions::default());
+
+        self.outline = simplify::ramer_douglas_peucker(&outline, tolerance);
+        self.points = PointBuffer::from(simplify::ramer_douglas_peucker(&points, tolerance));
+    }
+
+    /// The stroke moved onto the fitted spline, flattened within `tolerance`, with the
+    /// pressure and time of the samples carried over by how far along the stroke they
+    /// are. It passes through the spline's corners as well as a point for every sample,
+    /// so neither the curve's shape nor the pen's detail is lost. Strokes without a
+    /// spline or a length keep their samples.
+    fn curve_samples(&self, tolerance: f32) -> Vec<StrokePoint> {
+        let curve = self.polygon(tolerance);
+        let along_samples = distances(self.samples.iter().map(|s| s.point));
+        let along_curve = distances(curve.iter().copied());
+
+        let (Some(&sample_length), Some(&curve_length)) =
+            (along_samples.last(), along_curve.last())
+        else {
+            return self.samples.clone();
+        };
+        if self.curve.is_empty() || sample_length == 0.0 || curve_length == 0.0 {
+            return self.samples.clone();
+        }
+
+        let mut fractions: Vec<f32> = along_samples
+            .iter()
+            .map(|d| d / sample_length)
+            .chain(along_curve.iter().map(|d| d / curve_length))
+            .collect();
+        fractions.sort_by(f32::total_cmp);
+        fractions.dedup();
+
+        fractions
+            .into_iter()
+            .map(|fraction| {
+                let (i, t) = locate(&along_curve, fraction * curve_length);
+                let (j, u) = locate(&along_samples, fraction * sample_length);
+                let (a, b) = (self.samples[j], self.samples[j + 1]);
+
+                StrokePoint::new(
+                    curve[i].lerp(curve[i + 1], t),
+                    a.pressure + (b.pressure - a.pressure) * u,
+                    a.time + (b.time - a.time) * u as f64,
+                )
+            })
+            .collect()
+    }
+
+    /// Whether the stroke ends where it began, so it can be filled.
+    pub fn is_closed(&self) -> bool {
+        match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => {
+                self.points.len() >= 3 && first.euclid_dist(last) <= CLOSE_TOLERANCE
+            }
+            _ => false,
+        }
+    }
+
+    /// The stroke as a polygon, following the fitted spline within `tolerance` when finished.
+    pub fn polygon(&self, tolerance: f32) -> Vec<CanvasPoint> {
+        if self.curve.is_empty() {
+            return self.points.to_vec();
+        }
+
+        let mut points = vec![self.curve[0].from];
+        for c in &self.curve {
+            points.extend(c.flatten(tolerance).into_iter().skip(1));
+        }
+
+        points
+    }
+}
+
+/// How far along the polyline through `points` each of them is.
+fn distances(points: impl Iterator<Item = CanvasPoint>) -> Vec<f32> {
+    let mut previous = None;
+
+    points
+        .scan(0.0, |length, p| {
+            *length += previous.map_or(0.0, |q: CanvasPoint| q.euclid_dist(p));
+            previous = Some(p);
+            Some(*length)
+        })
+        .collect()
+}
+
+/// The edge of a polyline `distance` along it, given how far along each of its points
+/// is, and how far across that edge the distance falls. The polyline needs an edge.
+fn locate(distances: &[f32], distance: f32) -> (usize, f32) {
+    let i = distances
+        .partition_point(|&d| d <= distance)
+        .clamp(1, distances.len() - 1)
+        - 1;
+    let span = distances[i + 1] - distances[i];
+
+    let t = if span > 0.0 {
+        ((distance - distances[i]) / span).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    (i, t)
+}
+
+impl ShapeGeometry for Freehand {
+    /// The centreline's bounds, widened to the ink once the stroke is finished.
+    fn bounds(&self) -> Aabb {
+        let centreline = self
+            .points
+            .bounds()
+            .expect("freehand always holds its starting point");
+
+        match Aabb::from_points(&self.outline) {
+            Some(ink) => centreline.union(ink),
+            None => centreline,
+        }
+    }
+
+    /// Whether `point` is within `tolerance` of the stroke. Callers scale the tolerance by
+    /// the camera zoom so a click is equally forgiving at any zoom level.
+    fn hit_test(&self, point: CanvasPoint, tolerance: f32) -> bool {
+        self.bounds().expand(tolerance).contains(point)
+            && self.points.polyline_distance(point) <= tolerance
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        self.bounds().intersects(rect)
+            && (self.points.iter().any(|p| rect.contains(p))
+                || self.points.segments().any(|s| s.intersects_aabb(rect)))
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.transform(&Transform::translate(offset));
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        self.points.transform(transform);
+
+        for s in &mut self.samples {
+            s.point = transform.apply(s.point);
+        }
+        for p in &mut self.outline {
+            *p = transform.apply(*p);
+        }
+
+        for c in &mut self.curve {
+            *c = CubicBezier::new(
+                transform.apply(c.from),
+                transform.apply(c.ctrl1),
+                transform.apply(c.ctrl2),
+                transform.apply(c.to),
+            );
+        }
+    }
+
+    /// The ink as SVG path data for a single filled path, whose width follows the pen
+    /// pressure and speed. Unfinished strokes are outlined afresh on every call.
+    fn to_path(&self) -> String {
+        if self.outline.is_empty() {
+            stroke::svg_path(&stroke::outline(&self.samples, &StrokeOptions::default()))
+        } else {
+            stroke::svg_path(&self.outline)
+        }
+    }
+
+    fn render(&self) -> SvgElement {
+        self.style.apply(
+            SvgElement::new("path")
+                .with("d", self.to_path())
+                .with("fill-rule", "nonzero"),
+        )
     }
 }
 
@@ -64,8 +553,8 @@ impl Freehand {
 pub struct Circle {
     pub center: CanvasPoint,
     pub radius: f32,
+    pub style: Style,
     pub selected: bool,
-    pub temp_center: Option<CanvasPoint>,
 }
 
 impl Circle {
@@ -73,21 +562,925 @@ impl Circle {
         Self {
             center,
             radius,
+            style: Style::default(),
             selected,
-            temp_center: None,
         }
     }
 
-    pub fn is_inside(&self, global_pointer: CanvasPoint) -> bool {
-        self.center.euclid_dist(global_pointer) <= self.radius
+    /// The circle as an ellipse with equal radii.
+    pub fn ellipse(&self) -> math::Ellipse {
+        math::Ellipse::circle(self.center, self.radius)
+    }
+
+    /// The circle as a polygon whose edges stay within `tolerance` of it.
+    pub fn polygon(&self, tolerance: f32) -> Vec<CanvasPoint> {
+        self.ellipse().polygon(tolerance)
     }
+}
+
+impl ShapeGeometry for Circle {
+    fn bounds(&self) -> Aabb {
+        self.ellipse().bounds()
+    }
+
+    fn hit_test(&self, point: CanvasPoint, _tolerance: f32) -> bool {
+        self.ellipse().contains(point)
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        self.ellipse().intersects_aabb(rect)
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.center = self.center + offset;
+    }
+
+    /// Scales the radius by the square root of the area scale. [`Shape`] turns circles
+    /// into ellipses instead when the scale is not uniform.
+    fn transform(&mut self, transform: &Transform) {
+        self.center = transform.apply(self.center);
+        self.radius *= transform.determinant().abs().sqrt();
+    }
+
+    fn to_path(&self) -> String {
+        let (x, y) = self.center.coord();
+        let r = self.radius;
+        let (left, right) = (x - r, x + r);
+
+        format!("M {left} {y} A {r} {r} 0 1 0 {right} {y} A {r} {r} 0 1 0 {left} {y} Z")
+    }
+
+    fn render(&self) -> SvgElement {
+        let (x, y) = self.center.coord();
+
+        self.style.apply(
+            SvgElement::new("circle")
+                .with("cx", x)
+                .with("cy", y)
+                .with("r", self.radius),
+        )
+    }
+}
+
+/// An ellipse with independent radii, turned about its centre. Drawn by dragging out the
+/// box it is inscribed in, like a rectangle.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ellipse {
+    pub center: CanvasPoint,
+    pub radii: CanvasPoint,
+    /// Radians about the centre, clockwise on screen.
+    pub rotation: f32,
+    pub style: Style,
+    pub selected: bool,
+}
+
+impl Ellipse {
+    pub fn new(center: CanvasPoint, radii: CanvasPoint, selected: bool) -> Self {
+        Self {
+            center,
+            radii,
+            rotation: 0.0,
+            style: Style::default(),
+            selected,
+        }
+    }
+
+    /// The ellipse inscribed in the box from `position` by `width_height`, which may be
+    /// negative.
+    pub fn from_box(position: CanvasPoint, width_height: CanvasPoint, selected: bool) -> Self {
+        let e = math::Ellipse::from_aabb(Aabb::from_position_size(position, width_height));
+
+        Self::new(e.center, e.radii, selected)
+    }
+
+    pub fn ellipse(&self) -> math::Ellipse {
+        math::Ellipse::new(self.center, self.radii, self.rotation)
+    }
+
+    /// The ellipse as a polygon whose edges stay within `tolerance` of it.
+    pub fn polygon(&self, tolerance: f32) -> Vec<CanvasPoint> {
+        self.ellipse().polygon(tolerance)
+    }
+}
+
+impl ShapeGeometry for Ellipse {
+    fn bounds(&self) -> Aabb {
+        self.ellipse().bounds()
+    }
+
+    fn hit_test(&self, point: CanvasPoint, _tolerance: f32) -> bool {
+        self.ellipse().contains(point)
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        self.ellipse().intersects_aabb(rect)
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.center = self.center + offset;
+    }
+
+    /// Exact for any transform, since the image of an ellipse is an ellipse.
+    fn transform(&mut self, transform: &Transform) {
+        let e = self.ellipse().transformed(transform);
+
+        self.center = e.center;
+        self.radii = e.radii;
+        self.rotation = e.rotation;
+    }
+
+    fn to_path(&self) -> String {
+        let e = self.ellipse();
+        let (rx, ry) = self.radii.coord();
+        let degrees = self.rotation.to_degrees();
+        let (x1, y1) = e.point_at(0.0).coord();
+        let (x2, y2) = e.point_at(std::f32::consts::PI).coord();
+
+        format!(
+            "M {x1} {y1} A {rx} {ry} {degrees} 1 0 {x2} {y2} A {rx} {ry} {degrees} 1 0 {x1} {y1} Z"
+        )
+    }
+
+    fn render(&self) -> SvgElement {
+        let (x, y) = self.center.coord();
+        let (rx, ry) = self.radii.coord();
+
+        self.style.apply(
+            SvgElement::new("ellipse")
+                .with("cx", x)
+                .with("cy", y)
+                .with("rx", rx)
+                .with("ry", ry)
+                .with(
+                    "transform",
+                    format!("rotate({} {x} {y})", self.rotation.to_degrees()),
+                ),
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlign {
+    /// The SVG `text-anchor` that lines up each line this way.
+    pub fn text_anchor(self) -> &'static str {
+        match self {
+            TextAlign::Left => "start",
+            TextAlign::Center => "middle",
+            TextAlign::Right => "end",
+        }
+    }
+
+    /// The CSS `text-align` that lines up each line this way.
+    pub fn css(self) -> &'static str {
+        match self {
+            TextAlign::Left => "left",
+            TextAlign::Center => "center",
+            TextAlign::Right => "right",
+        }
+    }
+}
+
+/// Lines of text laid out in a box whose top-left corner is `position`.
+///
+/// The editor has no font metrics, so the box is estimated from the number of characters
+/// on the longest line and the number of lines. Glyphs may spill over it a little, but it
+/// keeps hit testing and selection independent of the browser.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Text {
+    pub position: CanvasPoint,
+    pub content: String,
+    pub font_family: String,
+    pub font_size: f32,
+    pub align: TextAlign,
+    /// The size of the laid-out box, kept in step with the content and font size by
+    /// [`Text::layout`].
+    pub width_height: CanvasPoint,
+    pub style: Style,
+    pub selected: bool,
+}
+
+impl Text {
+    pub const DEFAULT_FONT_FAMILY: &'static str = "sans-serif";
+    pub const DEFAULT_FONT_SIZE: f32 = 20.0;
+    /// The distance between baselines, in ems.
+    pub const LINE_HEIGHT: f32 = 1.2;
+    /// The estimated width of a character, in ems.
+    pub const AVERAGE_ADVANCE: f32 = 0.6;
+    /// The depth of the first baseline below the top of the box, in ems, with half the
+    /// leading above the ascent.
+    pub const BASELINE: f32 = 0.9;
+
+    pub fn new(
+        position: CanvasPoint,
+        content: impl Into<String>,
+        font_family: impl Into<String>,
+        font_size: f32,
+        align: TextAlign,
+        selected: bool,
+    ) -> Self {
+        let mut text = Self {
+            position,
+            content: content.into(),
+            font_family: font_family.into(),
+            font_size,
+            align,
+            width_height: CanvasPoint::splat(0.0),
+            style: Style::ink(),
+            selected,
+        };
+        text.layout();
+
+        text
+    }
+
+    /// Empty text in the default font, for typing into.
+    pub fn empty(position: CanvasPoint, selected: bool) -> Self {
+        Self::new(
+            position,
+            "",
+            Self::DEFAULT_FONT_FAMILY,
+            Self::DEFAULT_FONT_SIZE,
+            TextAlign::default(),
+            selected,
+        )
+    }
+
+    /// The content split into lines. Empty content is one empty line.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.content.split('\n')
+    }
+
+    pub fn set_content(&mut self, content: impl Into<String>) {
+        self.content = content.into();
+        self.layout();
+    }
+
+    /// Resizes the box to fit the content. It is always at least one character wide, so
+    /// empty text can still be clicked.
+    pub fn layout(&mut self) {
+        let columns = self.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let rows = self.lines().count();
+
+        self.width_height = CanvasPoint::new(
+            columns.max(1) as f32 * Self::AVERAGE_ADVANCE * self.font_size,
+            rows as f32 * Self::LINE_HEIGHT * self.font_size,
+        );
+    }
+
+    /// Where each line's anchor sits horizontally.
+    fn anchor_x(&self) -> f32 {
+        let (x, _) = self.position.coord();
+        let (w, _) = self.width_height.coord();
+
+        match self.align {
+            TextAlign::Left => x,
+            TextAlign::Center => x + w / 2.0,
+            TextAlign::Right => x + w,
+        }
+    }
+}
+
+impl ShapeGeometry for Text {
+    fn bounds(&self) -> Aabb {
+        Aabb::from_position_size(self.position, self.width_height)
+    }
+
+    fn hit_test(&self, point: CanvasPoint, _tolerance: f32) -> bool {
+        self.bounds().contains(point)
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        self.bounds().intersects(rect)
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.position = self.position + offset;
+    }
+
+    /// Text stays upright: the box's centre follows `transform` and the font size scales
+    /// by the square root of the area scale.
+    fn transform(&mut self, transform: &Transform) {
+        let center = transform.apply(self.bounds().center());
+
+        self.font_size *= transform.determinant().abs().sqrt();
+        self.layout();
+        self.position = center - self.width_height / CanvasPoint::splat(2.0);
+    }
+
+    fn to_path(&self) -> String {
+        let (x, y) = self.position.coord();
+        let (w, h) = self.width_height.coord();
+
+        format!("M {x} {y} h {w} v {h} h {} Z", -w)
+    }
+
+    fn render(&self) -> SvgElement {
+        let x = self.anchor_x();
+        let (_, top) = self.position.coord();
+
+        self.lines().enumerate().fold(
+            self.style.apply(
+                SvgElement::new("text")
+                    .with("font-family", &self.font_family)
+                    .with("font-size", self.font_size)
+                    .with("text-anchor", self.align.text_anchor())
+                    .with("style", "white-space: pre"),
+            ),
+            |text, (i, line)| {
+                let baseline =
+                    top + (i as f32 * Self::LINE_HEIGHT + Self::BASELINE) * self.font_size;
+
+                text.with_child(
+                    SvgElement::new("tspan")
+                        .with("x", x)
+                        .with("y", baseline)
+                        .with_text(line),
+                )
+            },
+        )
+    }
+}
+
+/// A filled region made from other shapes by boolean operations. The contours are
+/// filled with the nonzero rule, so inner contours winding the other way are holes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Compound {
+    pub contours: Vec<Vec<CanvasPoint>>,
+    pub style: Style,
+    pub selected: bool,
+}
+
+impl Compound {
+    pub fn new(contours: Vec<Vec<CanvasPoint>>, selected: bool) -> Self {
+        Self {
+            contours,
+            style: Style::default(),
+            selected,
+        }
+    }
+
+    /// Folds `operation` over the outlines of `shapes` in order, so a difference cuts every
+    /// later shape out of the first. The result takes the first shape's style. Needs at
+    /// least two shapes with outlines, and returns `None` when the result is empty.
+    pub fn combine<'a>(
+        operation: Operation,
+        shapes: impl IntoIterator<Item = &'a Shape>,
+        tolerance: f32,
+    ) -> Option<Self> {
+        let mut operands = shapes
+            .into_iter()
+            .filter_map(|s| Some((s.style(), s.outline(tolerance)?)));
+        let (style, first) = operands.next()?;
+        let (_, second) = operands.next()?;
+        let outlines = operands.map(|(_, o)| o);
+
+        let contours = outlines.fold(boolean::apply(operation, &first, &second), |acc, o| {
+            boolean::apply(operation, &acc, &o)
+        });
+
+        (!contours.is_empty()).then(|| Self {
+            style: style.clone(),
+            ..Self::new(contours, true)
+        })
+    }
+
+    fn winding_number(&self, point: CanvasPoint) -> i32 {
+        self.contours
+            .iter()
+            .map(|c| polygon::winding_number(c, point))
+            .sum()
+    }
+}
+
+impl ShapeGeometry for Compound {
+    fn bounds(&self) -> Aabb {
+        self.contours
+            .iter()
+            .filter_map(|c| Aabb::from_points(c))
+            .reduce(|a, b| a.union(b))
+            .expect("a compound always has a contour")
+    }
+
+    fn hit_test(&self, point: CanvasPoint, _tolerance: f32) -> bool {
+        self.winding_number(point) != 0
+    }
+
+    fn intersects_rect(&self, rect: Aabb) -> bool {
+        self.bounds().intersects(rect)
+            && (self.winding_number(rect.center()) != 0
+                || self
+                    .contours
+                    .iter()
+                    .flat_map(|c| polygon::edges(c))
+                    .any(|e| e.intersects_aabb(rect)))
+    }
+
+    fn translate(&mut self, offset: CanvasPoint) {
+        self.transform(&Transform::translate(offset));
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        self.contours
+            .iter_mut()
+            .for_each(|c| transform.apply_in_place(c));
+    }
+
+    fn to_path(&self) -> String {
+        self.contours
+            .iter()
+            .map(|c| {
+                let points = c
+                    .iter()
+                    .map(|p| {
+                        let (x, y) = p.coord();
+                        format!("{x} {y}")
+                    })
+                    .collect::<Vec<_>>();
+
+                format!("M {} Z", points.join(" L "))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn render(&self) -> SvgElement {
+        self.style.apply(
+            SvgElement::new("path")
+                .with("d", self.to_path())
+                .with("fill-rule", "nonzero"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::PrimitiveKind;
+
+    #[test]
+    fn rectangle_bounds() {
+        let r = Rectangle::new(
+            CanvasPoint::new(10.0, 20.0),
+            CanvasPoint::new(5.0, 5.0),
+            false,
+        );
+
+        assert_eq!(
+            r.bounds(),
+            Aabb::new(CanvasPoint::new(10.0, 20.0), CanvasPoint::new(15.0, 25.0))
+        );
+        assert!(r.intersects_rect(Aabb::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(12.0, 21.0)
+        )));
+        assert!(!r.intersects_rect(Aabb::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(9.0, 30.0)
+        )));
+    }
+
+    #[test]
+    fn circle_bounds() {
+        let c = Circle::new(CanvasPoint::new(0.0, 0.0), 10.0, false);
+
+        assert_eq!(
+            Shape::Circle(c.clone()).bounds(),
+            Aabb::new(CanvasPoint::new(-10.0, -10.0), CanvasPoint::new(10.0, 10.0))
+        );
+
+        // the box corner is inside the circle's bounds but outside the circle
+        let corner = Aabb::new(CanvasPoint::new(8.0, 8.0), CanvasPoint::new(20.0, 20.0));
+        assert!(c.bounds().intersects(corner));
+        assert!(!c.intersects_rect(corner));
+    }
+
+    #[test]
+    fn ellipse_hit_test() {
+        // a tall ellipse inscribed in a 20 × 40 box
+        let e = Ellipse::from_box(
+            CanvasPoint::new(20.0, 40.0),
+            CanvasPoint::new(-20.0, -40.0),
+            false,
+        );
+        assert_eq!(e.radii, CanvasPoint::new(10.0, 20.0));
+        assert_eq!(
+            e.bounds(),
+            Aabb::new(CanvasPoint::new(0.0, 0.0), CanvasPoint::new(20.0, 40.0))
+        );
+
+        assert!(e.hit_test(CanvasPoint::new(10.0, 1.0), 0.0));
+        assert!(!e.hit_test(CanvasPoint::new(2.0, 4.0), 0.0));
+        // the box corner is inside the ellipse's bounds but outside the ellipse
+        let corner = Aabb::new(CanvasPoint::new(17.0, 35.0), CanvasPoint::new(30.0, 50.0));
+        assert!(e.bounds().intersects(corner));
+        assert!(!e.intersects_rect(corner));
+        assert!(e.intersects_rect(Aabb::new(
+            CanvasPoint::new(15.0, 30.0),
+            CanvasPoint::new(30.0, 50.0)
+        )));
+    }
+
+    #[test]
+    fn freehand_hit_test() {
+        let mut f = Freehand::new(CanvasPoint::new(0.0, 0.0), false);
+        f.points.push(CanvasPoint::new(100.0, 0.0));
+
+        assert!(f.hit_test(CanvasPoint::new(50.0, 3.0), 4.0));
+        assert!(!f.hit_test(CanvasPoint::new(50.0, 5.0), 4.0));
+        assert!(f.intersects_rect(Aabb::new(
+            CanvasPoint::new(40.0, -10.0),
+            CanvasPoint::new(60.0, 10.0)
+        )));
+        assert!(!f.intersects_rect(Aabb::new(
+            CanvasPoint::new(40.0, 1.0),
+            CanvasPoint::new(60.0, 10.0)
+        )));
+    }
+
+    #[test]
+    fn freehand_path() {
+        let mut f = Freehand::new(CanvasPoint::new(0.0, 0.0), false);
+        // a lone point is a filled dot
+        assert!(f.to_path().starts_with("M ") && f.to_path().ends_with(" Z"));
+
+        f.push(StrokePoint::new(CanvasPoint::new(3.0, 0.0), 0.5, 16.0));
+        f.push(StrokePoint::new(CanvasPoint::new(6.0, 0.0), 0.5, 32.0));
+        assert!(f.outline.is_empty());
+        let drawing = f.to_path();
+
+        f.finish(0.5);
+        assert!(!f.outline.is_empty());
+        assert_eq!(f.to_path(), stroke::svg_path(&f.outline));
+        assert_eq!(f.to_path().matches('M').count(), 1);
+        assert_ne!(f.to_path(), drawing);
+    }
+
+    #[test]
+    fn freehand_pressure() {
+        let mut f = Freehand::from_sample(
+            StrokePoint::new(CanvasPoint::new(0.0, 0.0), 0.0, 0.0),
+            false,
+        );
+        (1..=60).for_each(|i| {
+            f.push(StrokePoint::new(
+                CanvasPoint::new(i as f32, 0.0),
+                i as f32 / 60.0,
+                i as f64 * 16.0,
+            ))
+        });
+        f.finish(0.1);
+
+        assert_eq!(f.samples.len(), 61);
+        assert_eq!(f.points.len(), 2);
+
+        // the ink is thin where the pen pressed lightly and thick where it pressed hard
+        let inked = |x: f32, y: f32| polygon::contains(&f.outline, CanvasPoint::new(x, y));
+        assert!(inked(10.0, 1.0) && !inked(10.0, 2.0));
+        assert!(inked(50.0, 2.0) && inked(50.0, -2.0));
+        assert!(f
+            .bounds()
+            .contains_aabb(Aabb::from_points(&f.outline).unwrap()));
+    }
+
+    #[test]
+    fn freehand_ink_follows_curve() {
+        let mut f = Freehand::new(CanvasPoint::new(0.0, 0.0), false);
+        (1..=50).for_each(|i| {
+            f.push(StrokePoint::new(
+                CanvasPoint::new(i as f32, (i % 2) as f32),
+                DEFAULT_PRESSURE,
+                i as f64 * 16.0,
+            ))
+        });
+        f.finish(1.0);
+
+        // every sample is on the fitted curve, in order, and the ink is built from them
+        let curve = PointBuffer::from(f.polygon(1.0));
+        let samples = f.curve_samples(1.0);
+        assert!(samples.len() >= f.samples.len());
+        assert!(samples
+            .iter()
+            .all(|s| curve.polyline_distance(s.point) <= 1e-3));
+        assert!(samples.windows(2).all(|w| w[0].time <= w[1].time));
+        assert_eq!(samples.first().map(|s| s.time), Some(0.0));
+        assert_eq!(samples.last().map(|s| s.time), Some(800.0));
+
+        let ink = stroke::outline(&samples, &StrokeOptions::default());
+        assert_eq!(f.outline, simplify::ramer_douglas_peucker(&ink, 1.0));
+    }
+
+    #[test]
+    fn freehand_finish() {
+        let mut f = Freehand::new(CanvasPoint::new(0.0, 0.0), false);
+        (1..=50).for_each(|i| {
+            f.points
+                .push(CanvasPoint::new(i as f32, (i % 2) as f32 * 0.2))
+        });
+        f.points.push(CanvasPoint::new(50.0, 50.0));
+        f.finish(0.5);
+
+        assert_eq!(
+            f.points.to_vec(),
+            vec![
+                CanvasPoint::new(0.0, 0.0),
+                CanvasPoint::new(50.0, 0.0),
+                CanvasPoint::new(50.0, 50.0)
+            ]
+        );
+        assert_eq!(f.curve.first().map(|c| c.from), f.points.first());
+        assert_eq!(f.curve.last().map(|c| c.to), f.points.last());
+    }
+
+    #[test]
+    fn freehand_bounds() {
+        let mut f = Freehand::new(CanvasPoint::new(3.0, 3.0), false);
+        f.points.push(CanvasPoint::new(-1.0, 7.0));
+
+        assert_eq!(
+            f.bounds(),
+            Aabb::new(CanvasPoint::new(-1.0, 3.0), CanvasPoint::new(3.0, 7.0))
+        );
+    }
+
+    #[test]
+    fn translate() {
+        let offset = CanvasPoint::new(5.0, -2.0);
+        let mut f = Freehand::new(CanvasPoint::new(0.0, 0.0), false);
+        f.push(StrokePoint::new(CanvasPoint::new(4.0, 4.0), 0.5, 16.0));
+        f.finish(0.1);
+
+        for mut s in [
+            Shape::Rectangle(Rectangle::new(
+                CanvasPoint::new(1.0, 1.0),
+                CanvasPoint::new(2.0, 3.0),
+                false,
+            )),
+            Shape::Circle(Circle::new(CanvasPoint::new(0.0, 0.0), 2.0, false)),
+            Shape::Freehand(f.clone()),
+        ] {
+            let before = s.bounds();
+            s.translate(offset);
+            assert_eq!(s.bounds(), before.translate(offset));
+        }
+    }
+
+    #[test]
+    fn transform_keeps_parametric_shapes() {
+        let mut r = Shape::Rectangle(Rectangle::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(20.0, 2.0),
+            false,
+        ));
+        r.rotate(std::f32::consts::FRAC_PI_2);
+
+        let Shape::Rectangle(rotated) = &r else {
+            unreachable!()
+        };
+        assert!((rotated.rotation - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(rotated.width_height, CanvasPoint::new(20.0, 2.0));
+        let bounds = r.bounds();
+        assert!(bounds.min.approx_eq(CanvasPoint::new(9.0, -9.0), 1e-5));
+        assert!(bounds.max.approx_eq(CanvasPoint::new(11.0, 11.0), 1e-5));
+
+        let mut c = Circle::new(CanvasPoint::new(1.0, 1.0), 2.0, false);
+        c.transform(&Transform::scale(CanvasPoint::splat(3.0)));
+        assert_eq!((c.center, c.radius), (CanvasPoint::new(3.0, 3.0), 6.0));
+        assert_eq!(c.render().tag, "circle");
+        assert_eq!(c.render().attribute("r"), Some("6"));
+        assert!(c.to_path().starts_with("M -3 3 A 6 6"));
+
+        let mut e = Ellipse::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(20.0, 10.0),
+            false,
+        );
+        e.transform(&Transform::rotate(std::f32::consts::FRAC_PI_2));
+        assert!((e.rotation - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(e.radii, CanvasPoint::new(20.0, 10.0));
+        assert!(e.hit_test(CanvasPoint::new(0.0, 19.0), 0.0));
+        assert_eq!(e.render().tag, "ellipse");
+        assert_eq!(e.render().attribute("transform"), Some("rotate(90 0 0)"));
+    }
+
+    #[test]
+    fn stretched_circle_becomes_ellipse() {
+        let mut s = Shape::Circle(Circle::new(CanvasPoint::new(0.0, 0.0), 10.0, true));
+        s.transform(&Transform::scale(CanvasPoint::splat(2.0)));
+        assert!(matches!(&s, Shape::Circle(c) if c.radius == 20.0));
+
+        s.transform(&Transform::scale(CanvasPoint::new(1.0, 0.5)));
+        let Shape::Ellipse(e) = &s else {
+            panic!("expected an ellipse, got {s:?}")
+        };
+        assert!(e.selected);
+        assert!(e.radii.approx_eq(CanvasPoint::new(20.0, 10.0), 1e-4));
+        assert!(s
+            .bounds()
+            .min
+            .approx_eq(CanvasPoint::new(-20.0, -10.0), 1e-4));
+    }
+
+    #[test]
+    fn from_tool() {
+        let (position, drag) = (CanvasPoint::new(1.0, 2.0), CanvasPoint::new(3.0, 4.0));
+
+        assert_eq!(
+            Shape::from_tool(Tool::Circle, position, drag, false),
+            Some(Shape::Circle(Circle::new(position, 5.0, false)))
+        );
+        assert!(matches!(
+            Shape::from_tool(Tool::Rect, position, drag, true),
+            Some(Shape::Rectangle(r)) if r.selected
+        ));
+        assert_eq!(
+            Shape::from_tool(Tool::Ellipse, position, drag, false),
+            Some(Shape::Ellipse(Ellipse::new(
+                CanvasPoint::new(2.5, 4.0),
+                CanvasPoint::new(1.5, 2.0),
+                false
+            )))
+        );
+        assert!(matches!(
+            Shape::from_tool(Tool::Arrow, position, drag, false),
+            Some(Shape::Arrow(a)) if a.line.end() == CanvasPoint::new(4.0, 6.0)
+        ));
+        assert!(matches!(
+            Shape::from_tool(Tool::Primitive(PrimitiveKind::STAR), position, drag, false),
+            Some(Shape::Primitive(p)) if p.kind == PrimitiveKind::STAR && p.width_height == drag
+        ));
+        assert_eq!(
+            Shape::from_tool(Tool::Freehand, position, drag, false),
+            None
+        );
+    }
+
+    #[test]
+    fn compound_difference() {
+        let mut rect = Shape::Rectangle(Rectangle::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(40.0, 20.0),
+            true,
+        ));
+        rect.style_mut().fill = Some("red".to_string());
+        let hole = Shape::Circle(Circle::new(CanvasPoint::new(20.0, 10.0), 5.0, true));
+
+        let c = Compound::combine(Operation::Difference, [&rect, &hole], 0.1).unwrap();
+
+        assert_eq!(c.contours.len(), 2);
+        assert_eq!(c.bounds(), rect.bounds());
+        assert_eq!(&c.style, rect.style());
+        assert!(c.hit_test(CanvasPoint::new(2.0, 2.0), 0.0));
+        assert!(!c.hit_test(CanvasPoint::new(20.0, 10.0), 0.0));
+        assert!(c.intersects_rect(Aabb::new(
+            CanvasPoint::new(10.0, 5.0),
+            CanvasPoint::new(30.0, 15.0)
+        )));
+        assert!(!c.intersects_rect(Aabb::new(
+            CanvasPoint::new(19.0, 9.0),
+            CanvasPoint::new(21.0, 11.0)
+        )));
+
+        // a single shape, or an empty result, makes nothing
+        assert_eq!(Compound::combine(Operation::Union, [&rect], 0.1), None);
+        assert_eq!(
+            Compound::combine(Operation::Difference, [&hole, &rect], 0.1),
+            None
+        );
+    }
+
+    #[test]
+    fn open_freehand_has_no_outline() {
+        let mut f = Freehand::new(CanvasPoint::new(0.0, 0.0), false);
+        f.points.push(CanvasPoint::new(20.0, 0.0));
+        f.points.push(CanvasPoint::new(20.0, 20.0));
+        assert_eq!(Shape::Freehand(f.clone()).outline(0.5), None);
+
+        f.points.push(CanvasPoint::new(2.0, 2.0));
+        assert_eq!(Shape::Freehand(f).outline(0.5).map(|o| o[0].len()), Some(4));
+    }
+
+    #[test]
+    fn rotated_rectangle() {
+        let mut r = Rectangle::new(
+            CanvasPoint::new(-10.0, -1.0),
+            CanvasPoint::new(20.0, 2.0),
+            false,
+        );
+        r.rotation = std::f32::consts::FRAC_PI_2;
+
+        // now a tall thin bar through the origin
+        assert!(r.hit_test(CanvasPoint::new(0.0, 9.0), 0.0));
+        assert!(!r.hit_test(CanvasPoint::new(9.0, 0.0), 0.0));
+        assert!(r.bounds().contains(CanvasPoint::new(1.0, -10.0)));
+        assert!(r.intersects_rect(Aabb::new(
+            CanvasPoint::new(-2.0, 8.0),
+            CanvasPoint::new(2.0, 12.0)
+        )));
+        assert!(!r.intersects_rect(Aabb::new(
+            CanvasPoint::new(5.0, -2.0),
+            CanvasPoint::new(12.0, 2.0)
+        )));
+    }
+
+    #[test]
+    fn text_layout() {
+        let mut t = Text::empty(CanvasPoint::new(10.0, 10.0), false);
+        let em = Text::DEFAULT_FONT_SIZE;
+
+        // empty text is still one character wide
+        assert_eq!(t.width_height, CanvasPoint::new(0.6 * em, 1.2 * em));
+        assert!(t.hit_test(CanvasPoint::new(12.0, 12.0), 0.0));
+
+        t.set_content("abcd\nab");
+        assert_eq!(t.width_height, CanvasPoint::new(2.4 * em, 2.4 * em));
+        assert!(t.intersects_rect(Aabb::new(
+            CanvasPoint::new(0.0, 50.0),
+            CanvasPoint::new(20.0, 60.0)
+        )));
+
+        t.transform(&Transform::scale(CanvasPoint::splat(2.0)));
+        assert_eq!(t.font_size, 2.0 * em);
+        assert_eq!(t.bounds().center(), CanvasPoint::new(68.0, 68.0));
+    }
+
+    #[test]
+    fn text_render() {
+        let t = Text::new(
+            CanvasPoint::new(0.0, 0.0),
+            "one\ntwo",
+            "serif",
+            10.0,
+            TextAlign::Center,
+            false,
+        );
+        let element = t.render();
+
+        assert_eq!(element.tag, "text");
+        assert_eq!(element.attribute("font-family"), Some("serif"));
+        assert_eq!(element.attribute("text-anchor"), Some("middle"));
+
+        let lines: Vec<_> = element
+            .children
+            .iter()
+            .map(|tspan| {
+                (
+                    tspan.tag,
+                    tspan.attribute("x").unwrap(),
+                    tspan.attribute("y").unwrap(),
+                    tspan.text.as_deref().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [("tspan", "9", "9", "one"), ("tspan", "9", "21", "two")]
+        );
+    }
+
+    #[test]
+    fn anchors() {
+        let mut r = Shape::Rectangle(Rectangle::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(40.0, 20.0),
+            false,
+        ));
+        let corner = CanvasPoint::new(40.0, 20.0);
+        let anchor = r.anchor_at(corner).unwrap();
+        assert_eq!(anchor, CanvasPoint::new(1.0, 1.0));
+
+        // the anchor turns with the rectangle
+        r.rotate(std::f32::consts::FRAC_PI_2);
+        let turned = r.anchor_point(anchor).unwrap();
+        assert!(turned.approx_eq(CanvasPoint::new(10.0, 30.0), 1e-4));
+        assert!(r.anchor_at(turned).unwrap().approx_eq(anchor, 1e-5));
+
+        let c = Shape::Circle(Circle::new(CanvasPoint::new(10.0, 10.0), 5.0, false));
+        let anchor = c.anchor_at(CanvasPoint::new(15.0, 10.0)).unwrap();
+        assert_eq!(anchor, CanvasPoint::new(1.0, 0.0));
+        assert_eq!(c.anchor_point(anchor), Some(CanvasPoint::new(15.0, 10.0)));
 
-    pub fn intersects(&self, selection_box: (CanvasPoint, CanvasPoint)) -> bool {
-        // we need to find the closest point of the selection box to the center
-        let closest_box_point = self
-            .center
-            .clamp(selection_box.0, selection_box.0 + selection_box.1);
+        let mut e = Shape::Ellipse(Ellipse::from_box(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(40.0, 20.0),
+            false,
+        ));
+        let anchor = e.anchor_at(CanvasPoint::new(20.0, 20.0)).unwrap();
+        assert_eq!(anchor, CanvasPoint::new(0.0, 1.0));
+        e.rotate(std::f32::consts::FRAC_PI_2);
+        let turned = e.anchor_point(anchor).unwrap();
+        assert!(turned.approx_eq(CanvasPoint::new(10.0, 10.0), 1e-4));
 
-        closest_box_point.euclid_dist(self.center) <= self.radius
+        let line = Shape::Line(Line::straight(corner, corner, false));
+        assert_eq!(line.anchor_at(corner), None);
     }
 }
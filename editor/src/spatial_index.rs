@@ -0,0 +1,441 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap};
+
+use math::{Aabb, CanvasPoint};
+
+/// How many entries a leaf holds before it splits into quadrants.
+const NODE_CAPACITY: usize = 8;
+
+/// Quadrants no wider than this, in canvas units, never split further.
+const MIN_QUADRANT_SIZE: f32 = 1.0;
+
+/// The side of the first root quadrant, before it grows to fit what is inserted.
+const MIN_ROOT_SIZE: f32 = 64.0;
+
+/// A quadtree over shape bounds, keyed by shape id. Each entry lives in the smallest
+/// quadrant that fully contains it, and the root grows outwards whenever something lands
+/// outside it, so the canvas stays unbounded.
+///
+/// Queries only test bounds. Callers refine the candidates with the exact shape geometry.
+#[derive(Debug, Clone, Default)]
+pub struct SpatialIndex {
+    bounds: BTreeMap<u32, Aabb>,
+    root: Option<Node>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    quadrant: Aabb,
+    entries: Vec<(u32, Aabb)>,
+    children: Option<Box<[Node; 4]>>,
+}
+
+impl Node {
+    fn new(quadrant: Aabb) -> Self {
+        Self {
+            quadrant,
+            entries: Vec::new(),
+            children: None,
+        }
+    }
+
+    /// The four quadrants in row order: top left, top right, bottom left, bottom right.
+    fn quadrants(&self) -> [Aabb; 4] {
+        let (x0, y0) = self.quadrant.min.coord();
+        let (x1, y1) = self.quadrant.max.coord();
+        let (cx, cy) = self.quadrant.center().coord();
+
+        [
+            Aabb::new(CanvasPoint::new(x0, y0), CanvasPoint::new(cx, cy)),
+            Aabb::new(CanvasPoint::new(cx, y0), CanvasPoint::new(x1, cy)),
+            Aabb::new(CanvasPoint::new(x0, cy), CanvasPoint::new(cx, y1)),
+            Aabb::new(CanvasPoint::new(cx, cy), CanvasPoint::new(x1, y1)),
+        ]
+    }
+
+    fn insert(&mut self, id: u32, bounds: Aabb) {
+        if let Some(children) = &mut self.children {
+            match children
+                .iter_mut()
+                .find(|c| c.quadrant.contains_aabb(bounds))
+            {
+                Some(child) => child.insert(id, bounds),
+                // straddles the centre lines, so it stays here
+                None => self.entries.push((id, bounds)),
+            }
+            return;
+        }
+
+        self.entries.push((id, bounds));
+
+        if self.entries.len() > NODE_CAPACITY
+            && self.quadrant.width_height().one() > MIN_QUADRANT_SIZE
+        {
+            self.children = Some(Box::new(self.quadrants().map(Node::new)));
+
+            for (id, bounds) in std::mem::take(&mut self.entries) {
+                self.insert(id, bounds);
+            }
+        }
+    }
+
+    fn remove(&mut self, id: u32, bounds: Aabb) -> bool {
+        if let Some(i) = self.entries.iter().position(|&(e, _)| e == id) {
+            self.entries.swap_remove(i);
+            return true;
+        }
+
+        let Some(children) = &mut self.children else {
+            return false;
+        };
+
+        let removed = children
+            .iter_mut()
+            .filter(|c| c.quadrant.intersects(bounds))
+            .any(|c| c.remove(id, bounds));
+
+        // fold sparse leaves back into this node
+        let leaves = children.iter().all(|c| c.children.is_none());
+        let count = self.entries.len() + children.iter().map(|c| c.entries.len()).sum::<usize>();
+
+        if removed && leaves && count <= NODE_CAPACITY {
+            let children = self.children.take().expect("checked above");
+            self.entries
+                .extend(children.into_iter().flat_map(|c| c.entries));
+        }
+
+        removed
+    }
+
+    fn query(&self, area: Aabb, found: &mut Vec<u32>) {
+        if !self.quadrant.intersects(area) {
+            return;
+        }
+
+        found.extend(
+            self.entries
+                .iter()
+                .filter(|(_, bounds)| bounds.intersects(area))
+                .map(|&(id, _)| id),
+        );
+
+        if let Some(children) = &self.children {
+            children.iter().for_each(|c| c.query(area, found));
+        }
+    }
+
+    /// A parent twice the size with `self` as the quadrant facing away from `target`.
+    fn grow_towards(self, target: Aabb) -> Node {
+        let (w, h) = self.quadrant.width_height().coord();
+        let left = target.min.one() < self.quadrant.min.one();
+        let up = target.min.two() < self.quadrant.min.two();
+
+        let offset = CanvasPoint::new(if left { -w } else { 0.0 }, if up { -h } else { 0.0 });
+        let mut parent = Node::new(Aabb::from_position_size(
+            self.quadrant.min + offset,
+            CanvasPoint::new(2.0 * w, 2.0 * h),
+        ));
+
+        let mut children = parent.quadrants().map(Node::new);
+        children[left as usize + 2 * up as usize] = self;
+        parent.children = Some(Box::new(children));
+
+        parent
+    }
+}
+
+/// A node or entry waiting in the nearest neighbour queue, ordered so the heap pops the
+/// closest first, nodes before entries at equal distance, then the lowest id.
+struct Candidate<'a> {
+    distance: f32,
+    item: Item<'a>,
+}
+
+enum Item<'a> {
+    Node(&'a Node),
+    Entry(u32),
+}
+
+impl Item<'_> {
+    fn rank(&self) -> (u8, u32) {
+        match self {
+            Item::Node(_) => (0, 0),
+            Item::Entry(id) => (1, *id),
+        }
+    }
+}
+
+impl PartialEq for Candidate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate<'_> {}
+
+impl PartialOrd for Candidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then(other.item.rank().cmp(&self.item.rank()))
+    }
+}
+
+fn distance_to(bounds: Aabb, point: CanvasPoint) -> f32 {
+    bounds.closest_point(point).euclid_dist(point)
+}
+
+impl SpatialIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// The bounds `id` was last inserted with.
+    pub fn get(&self, id: u32) -> Option<Aabb> {
+        self.bounds.get(&id).copied()
+    }
+
+    /// Inserts `id`, or moves it when it is already indexed.
+    pub fn insert(&mut self, id: u32, bounds: Aabb) {
+        if self.get(id) == Some(bounds) {
+            return;
+        }
+        self.remove(id);
+
+        let root = self.root.get_or_insert_with(|| {
+            let (w, h) = bounds.width_height().coord();
+            let side = w.max(h).max(MIN_ROOT_SIZE);
+
+            Node::new(Aabb::from_position_size(
+                bounds.min,
+                CanvasPoint::splat(side),
+            ))
+        });
+
+        while !root.quadrant.contains_aabb(bounds) && root.quadrant.area().is_finite() {
+            let old = std::mem::replace(root, Node::new(root.quadrant));
+            *root = old.grow_towards(bounds);
+        }
+
+        root.insert(id, bounds);
+        self.bounds.insert(id, bounds);
+    }
+
+    pub fn remove(&mut self, id: u32) -> Option<Aabb> {
+        let bounds = self.bounds.remove(&id)?;
+
+        if let Some(root) = &mut self.root {
+            root.remove(id, bounds);
+        }
+
+        if self.bounds.is_empty() {
+            self.root = None;
+        }
+
+        Some(bounds)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Ids whose bounds touch `area`, in ascending order.
+    pub fn query_rect(&self, area: Aabb) -> Vec<u32> {
+        let mut found = Vec::new();
+
+        if let Some(root) = &self.root {
+            root.query(area, &mut found);
+        }
+        found.sort_unstable();
+
+        found
+    }
+
+    /// Ids whose bounds lie within `tolerance` of `point`, in ascending order.
+    pub fn query_point(&self, point: CanvasPoint, tolerance: f32) -> Vec<u32> {
+        self.query_rect(Aabb::new(point, point).expand(tolerance))
+    }
+
+    /// The id whose bounds are closest to `point`. Points inside several bounds pick the
+    /// lowest id.
+    pub fn nearest(&self, point: CanvasPoint) -> Option<u32> {
+        let mut queue = BinaryHeap::new();
+
+        if let Some(root) = &self.root {
+            queue.push(Candidate {
+                distance: distance_to(root.quadrant, point),
+                item: Item::Node(root),
+            });
+        }
+
+        while let Some(Candidate { item, .. }) = queue.pop() {
+            let node = match item {
+                Item::Entry(id) => return Some(id),
+                Item::Node(node) => node,
+            };
+
+            queue.extend(node.entries.iter().map(|&(id, bounds)| Candidate {
+                distance: distance_to(bounds, point),
+                item: Item::Entry(id),
+            }));
+
+            if let Some(children) = &node.children {
+                queue.extend(children.iter().map(|c| Candidate {
+                    distance: distance_to(c.quadrant, point),
+                    item: Item::Node(c),
+                }));
+            }
+        }
+
+        None
+    }
+}
+
+impl PartialEq for SpatialIndex {
+    /// Two indexes are equal when they hold the same bounds, however their trees are laid out.
+    fn eq(&self, other: &Self) -> bool {
+        self.bounds == other.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Aabb {
+        Aabb::from_position_size(CanvasPoint::new(x, y), CanvasPoint::splat(size))
+    }
+
+    /// A scattered grid of boxes, spread wide enough to grow the root several times.
+    fn scattered() -> Vec<(u32, Aabb)> {
+        (0..400u32)
+            .map(|i| {
+                let x = ((i * 37) % 400) as f32 * 5.0 - 1000.0;
+                let y = ((i * 91) % 400) as f32 * 3.0 - 600.0;
+
+                (i, square(x, y, (i % 7) as f32 * 4.0))
+            })
+            .collect()
+    }
+
+    fn brute_force(entries: &[(u32, Aabb)], area: Aabb) -> Vec<u32> {
+        entries
+            .iter()
+            .filter(|(_, b)| b.intersects(area))
+            .map(|&(id, _)| id)
+            .collect()
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let entries = scattered();
+        let mut index = SpatialIndex::new();
+        entries.iter().for_each(|&(id, b)| index.insert(id, b));
+
+        assert_eq!(index.len(), entries.len());
+
+        for area in [
+            square(-50.0, -50.0, 100.0),
+            square(-1000.0, -600.0, 30.0),
+            square(400.0, 300.0, 600.0),
+            square(5000.0, 5000.0, 10.0),
+        ] {
+            assert_eq!(index.query_rect(area), brute_force(&entries, area));
+        }
+
+        let point = CanvasPoint::new(12.0, 7.0);
+        assert_eq!(
+            index.query_point(point, 6.0),
+            brute_force(&entries, Aabb::new(point, point).expand(6.0))
+        );
+    }
+
+    #[test]
+    fn nearest() {
+        let entries = scattered();
+        let mut index = SpatialIndex::new();
+        entries.iter().for_each(|&(id, b)| index.insert(id, b));
+
+        for point in [
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(-3000.0, 2000.0),
+            CanvasPoint::new(333.0, -123.0),
+        ] {
+            let closest = entries
+                .iter()
+                .map(|&(_, b)| distance_to(b, point))
+                .fold(f32::INFINITY, f32::min);
+            let found = index.nearest(point).unwrap();
+
+            assert_eq!(distance_to(index.get(found).unwrap(), point), closest);
+        }
+
+        assert_eq!(
+            SpatialIndex::new().nearest(CanvasPoint::new(0.0, 0.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn move_and_remove() {
+        let mut index = SpatialIndex::new();
+        index.insert(1, square(0.0, 0.0, 10.0));
+        index.insert(2, square(100.0, 100.0, 10.0));
+
+        assert_eq!(index.query_point(CanvasPoint::new(5.0, 5.0), 0.0), vec![1]);
+
+        index.insert(1, square(200.0, 200.0, 10.0));
+        assert!(index
+            .query_point(CanvasPoint::new(5.0, 5.0), 0.0)
+            .is_empty());
+        assert_eq!(
+            index.query_point(CanvasPoint::new(205.0, 205.0), 0.0),
+            vec![1]
+        );
+        assert_eq!(index.len(), 2);
+
+        assert_eq!(index.remove(2), Some(square(100.0, 100.0, 10.0)));
+        assert_eq!(index.remove(2), None);
+        assert_eq!(index.query_rect(square(-1e4, -1e4, 2e4)), vec![1]);
+    }
+
+    #[test]
+    fn removal_collapses_the_tree() {
+        let entries = scattered();
+        let mut index = SpatialIndex::new();
+        entries.iter().for_each(|&(id, b)| index.insert(id, b));
+
+        for &(id, _) in entries.iter().filter(|(id, _)| id % 3 != 0) {
+            index.remove(id);
+        }
+
+        let remaining: Vec<(u32, Aabb)> =
+            entries.into_iter().filter(|(id, _)| id % 3 == 0).collect();
+        let area = square(-500.0, -500.0, 900.0);
+        assert_eq!(index.query_rect(area), brute_force(&remaining, area));
+
+        remaining.iter().for_each(|&(id, _)| {
+            index.remove(id);
+        });
+        assert!(index.is_empty());
+        assert_eq!(index, SpatialIndex::new());
+    }
+}
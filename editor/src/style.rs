@@ -0,0 +1,149 @@
+use crate::SvgElement;
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+impl LineCap {
+    pub fn svg(self) -> &'static str {
+        match self {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        }
+    }
+}
+
+impl LineJoin {
+    pub fn svg(self) -> &'static str {
+        match self {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        }
+    }
+}
+
+/// How a shape is painted. Colours are CSS colours, and `None` paints nothing.
+///
+/// Freehand strokes and text are drawn as filled outlines and glyphs, so their ink is
+/// the fill colour.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Style {
+    pub stroke: Option<String>,
+    pub fill: Option<String>,
+    /// In canvas units, so it scales with the zoom.
+    pub stroke_width: f32,
+    /// From 0, transparent, to 1, opaque.
+    pub opacity: f32,
+    /// Alternating dash and gap lengths in canvas units. Empty is a solid line.
+    pub dash: Vec<f32>,
+    pub line_cap: LineCap,
+    pub line_join: LineJoin,
+}
+
+impl Default for Style {
+    /// A black outline filled in orange.
+    fn default() -> Self {
+        Self {
+            stroke: Some("#000000".to_string()),
+            fill: Some("#fdba74".to_string()),
+            stroke_width: 1.0,
+            opacity: 1.0,
+            dash: Vec::new(),
+            line_cap: LineCap::default(),
+            line_join: LineJoin::default(),
+        }
+    }
+}
+
+impl Style {
+    /// Black ink without an outline, for freehand strokes and text.
+    pub fn ink() -> Self {
+        Self {
+            stroke: None,
+            fill: Some("#000000".to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// A black stroke with round ends and no fill, for lines and arrows.
+    pub fn line() -> Self {
+        Self {
+            fill: None,
+            stroke_width: 2.0,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the presentation attributes for this style on `element`.
+    pub fn apply(&self, element: SvgElement) -> SvgElement {
+        let paint = |colour: &Option<String>| colour.as_deref().unwrap_or("none").to_string();
+        let dash = if self.dash.is_empty() {
+            "none".to_string()
+        } else {
+            self.dash
+                .iter()
+                .map(f32::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        element
+            .with("stroke", paint(&self.stroke))
+            .with("fill", paint(&self.fill))
+            .with("stroke-width", self.stroke_width)
+            .with("opacity", self.opacity)
+            .with("stroke-dasharray", dash)
+            .with("stroke-linecap", self.line_cap.svg())
+            .with("stroke-linejoin", self.line_join.svg())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let style = Style {
+            stroke: Some("red".to_string()),
+            fill: None,
+            stroke_width: 2.5,
+            opacity: 0.5,
+            dash: vec![4.0, 2.0],
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Bevel,
+        };
+        let element = style.apply(SvgElement::new("path").with("fill", "blue"));
+
+        assert_eq!(element.attribute("stroke"), Some("red"));
+        assert_eq!(element.attribute("fill"), Some("none"));
+        assert_eq!(element.attribute("stroke-width"), Some("2.5"));
+        assert_eq!(element.attribute("opacity"), Some("0.5"));
+        assert_eq!(element.attribute("stroke-dasharray"), Some("4 2"));
+        assert_eq!(element.attribute("stroke-linecap"), Some("round"));
+        assert_eq!(element.attribute("stroke-linejoin"), Some("bevel"));
+
+        let solid = Style::ink().apply(SvgElement::new("path"));
+        assert_eq!(solid.attribute("stroke"), Some("none"));
+        assert_eq!(solid.attribute("stroke-dasharray"), Some("none"));
+    }
+}
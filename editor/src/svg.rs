@@ -0,0 +1,45 @@
+/// An SVG element described independently of any UI framework: its tag, attributes in
+/// order, and content.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SvgElement {
+    pub tag: &'static str,
+    pub attributes: Vec<(&'static str, String)>,
+    pub children: Vec<SvgElement>,
+    /// Character data, placed before any children.
+    pub text: Option<String>,
+}
+
+impl SvgElement {
+    pub fn new(tag: &'static str) -> Self {
+        Self {
+            tag,
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: None,
+        }
+    }
+
+    /// Adds an attribute, replacing any earlier value for `name`.
+    pub fn with(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.attributes.retain(|(n, _)| *n != name);
+        self.attributes.push((name, value.to_string()));
+        self
+    }
+
+    pub fn with_child(mut self, child: SvgElement) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn with_text(mut self, text: impl ToString) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
@@ -1,3 +1,5 @@
+use crate::PrimitiveKind;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Tool {
     Hand,
@@ -6,4 +8,9 @@ pub enum Tool {
     Select,
     Text,
     Circle,
+    Ellipse,
+    Line,
+    Arrow,
+    /// Diagram shapes, dragged out like rectangles.
+    Primitive(PrimitiveKind),
 }
@@ -0,0 +1,162 @@
+use crate::simd::{f32x4_le, i32x4_all_true, i32x4_shuffle};
+use crate::CanvasPoint;
+
+/// An axis-aligned bounding box, stored as its `min` and `max` corners.
+///
+/// Boxes are closed: points and boxes touching the boundary count as inside and
+/// overlapping.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Aabb {
+    pub min: CanvasPoint,
+    pub max: CanvasPoint,
+}
+
+impl Aabb {
+    pub fn new(min: CanvasPoint, max: CanvasPoint) -> Self {
+        Self { min, max }
+    }
+
+    /// Builds the box spanned by two opposite corners given in any order.
+    pub fn from_corners(p1: CanvasPoint, p2: CanvasPoint) -> Self {
+        Self::new(p1.min(p2), p1.max(p2))
+    }
+
+    pub fn from_position_size(position: CanvasPoint, width_height: CanvasPoint) -> Self {
+        Self::from_corners(position, position + width_height)
+    }
+
+    /// Returns the smallest box containing every point, or `None` for an empty slice.
+    pub fn from_points(points: &[CanvasPoint]) -> Option<Self> {
+        let (&first, rest) = points.split_first()?;
+
+        Some(rest.iter().fold(Self::new(first, first), |aabb, &p| {
+            Self::new(aabb.min.min(p), aabb.max.max(p))
+        }))
+    }
+
+    pub fn position(&self) -> CanvasPoint {
+        self.min
+    }
+
+    pub fn width_height(&self) -> CanvasPoint {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> CanvasPoint {
+        self.min.midpoint(self.max)
+    }
+
+    pub fn area(&self) -> f32 {
+        let (w, h) = self.width_height().coord();
+        w * h
+    }
+
+    pub fn union(&self, other: Aabb) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    /// Returns the overlapping region, or `None` if the boxes are disjoint.
+    pub fn intersection(&self, other: Aabb) -> Option<Self> {
+        self.intersects(other)
+            .then(|| Self::new(self.min.max(other.min), self.max.min(other.max)))
+    }
+
+    /// Grows the box by `margin` on every side. A negative margin shrinks it.
+    pub fn expand(&self, margin: f32) -> Self {
+        let margin = CanvasPoint::splat(margin);
+        Self::new(self.min - margin, self.max + margin)
+    }
+
+    pub fn translate(&self, offset: CanvasPoint) -> Self {
+        Self::new(self.min + offset, self.max + offset)
+    }
+
+    pub fn contains(&self, point: CanvasPoint) -> bool {
+        (self.min.cmple(point) & point.cmple(self.max)).all()
+    }
+
+    pub fn contains_aabb(&self, other: Aabb) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    /// The point of the box nearest to `point`, which is `point` itself when inside.
+    pub fn closest_point(&self, point: CanvasPoint) -> CanvasPoint {
+        point.clamp(self.min, self.max)
+    }
+
+    /// Overlap test in a single comparison: `(min, other.min) <= (other.max, max)` on all four lanes.
+    #[inline(always)]
+    pub fn intersects(&self, other: Aabb) -> bool {
+        let mins = i32x4_shuffle::<0, 1, 4, 5>(self.min.0, other.min.0);
+        let maxs = i32x4_shuffle::<0, 1, 4, 5>(other.max.0, self.max.0);
+
+        i32x4_all_true(f32x4_le(mins, maxs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn aabb(x1: f32, y1: f32, x2: f32, y2: f32) -> Aabb {
+        Aabb::new(CanvasPoint::new(x1, y1), CanvasPoint::new(x2, y2))
+    }
+
+    #[test]
+    fn from_corners() {
+        let b = Aabb::from_corners(CanvasPoint::new(10.0, 0.0), CanvasPoint::new(0.0, 5.0));
+
+        assert_eq!(b, aabb(0.0, 0.0, 10.0, 5.0));
+        assert_eq!(b.width_height(), CanvasPoint::new(10.0, 5.0));
+        assert_eq!(b.area(), 50.0);
+        assert_eq!(b.center(), CanvasPoint::new(5.0, 2.5));
+    }
+
+    #[test]
+    fn from_points() {
+        let points = [
+            CanvasPoint::new(1.0, 4.0),
+            CanvasPoint::new(-2.0, 3.0),
+            CanvasPoint::new(5.0, -1.0),
+        ];
+
+        assert_eq!(Aabb::from_points(&points), Some(aabb(-2.0, -1.0, 5.0, 4.0)));
+        assert_eq!(Aabb::from_points(&[]), None);
+    }
+
+    #[test]
+    fn intersects() {
+        let b = aabb(0.0, 0.0, 10.0, 10.0);
+
+        assert!(b.intersects(aabb(5.0, 5.0, 15.0, 15.0)));
+        assert!(b.intersects(aabb(10.0, 0.0, 20.0, 10.0)));
+        assert!(b.intersects(aabb(2.0, 2.0, 3.0, 3.0)));
+        assert!(!b.intersects(aabb(11.0, 0.0, 20.0, 10.0)));
+        assert!(!b.intersects(aabb(0.0, -5.0, 10.0, -1.0)));
+        assert!(!aabb(11.0, 0.0, 20.0, 10.0).intersects(b));
+    }
+
+    #[test]
+    fn union_and_intersection() {
+        let a = aabb(0.0, 0.0, 10.0, 10.0);
+        let b = aabb(5.0, -5.0, 15.0, 5.0);
+
+        assert_eq!(a.union(b), aabb(0.0, -5.0, 15.0, 10.0));
+        assert_eq!(a.intersection(b), Some(aabb(5.0, 0.0, 10.0, 5.0)));
+        assert_eq!(a.intersection(aabb(20.0, 20.0, 30.0, 30.0)), None);
+    }
+
+    #[test]
+    fn contains() {
+        let b = aabb(0.0, 0.0, 10.0, 10.0);
+
+        assert!(b.contains(CanvasPoint::new(10.0, 5.0)));
+        assert!(!b.contains(CanvasPoint::new(10.1, 5.0)));
+        assert!(b.contains_aabb(aabb(1.0, 1.0, 9.0, 9.0)));
+        assert!(!b.contains_aabb(aabb(1.0, 1.0, 11.0, 9.0)));
+        assert!(b.expand(1.0).contains(CanvasPoint::new(10.5, -0.5)));
+    }
+}
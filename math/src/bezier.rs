@@ -0,0 +1,723 @@
+//! Quadratic and cubic Bezier curves.
+//!
+//! Every evaluation works on whole `CanvasPoint`s, so the x and y polynomials are
+//! computed side by side in the same lanes.
+
+use crate::{Aabb, CanvasPoint};
+
+/// Number of uniform samples used to seed the nearest point search.
+const NEAREST_SAMPLES: usize = 16;
+const NEAREST_NEWTON_ITERATIONS: usize = 4;
+const MAX_ARC_LENGTH_DEPTH: usize = 16;
+/// The most segments a curve is flattened into, so a tolerance of zero or less, or a
+/// huge curve, still gives a finite polyline.
+const MAX_FLATTEN_SEGMENTS: usize = 1024;
+
+pub trait Bezier: Sized + Copy {
+    fn from(&self) -> CanvasPoint;
+
+    fn to(&self) -> CanvasPoint;
+
+    fn eval(&self, t: f32) -> CanvasPoint;
+
+    fn derivative(&self, t: f32) -> CanvasPoint;
+
+    fn second_derivative(&self, t: f32) -> CanvasPoint;
+
+    /// Splits the curve at `t` with de Casteljau's algorithm.
+    fn split(&self, t: f32) -> (Self, Self);
+
+    /// The tight bounding box, found from the roots of the derivative on each axis.
+    fn bounds(&self) -> Aabb;
+
+    /// Uniform parameter steps needed so no point on the curve strays more than
+    /// `tolerance` from the polyline, up to [`MAX_FLATTEN_SEGMENTS`].
+    fn flatten_segments(&self, tolerance: f32) -> usize;
+
+    /// Length of the control polygon, an upper bound on the arc length.
+    fn hull_length(&self) -> f32;
+
+    /// Approximates the curve with a polyline, endpoints included, that stays within `tolerance`.
+    fn flatten(&self, tolerance: f32) -> Vec<CanvasPoint> {
+        let n = self.flatten_segments(tolerance);
+
+        (0..=n).map(|i| self.eval(i as f32 / n as f32)).collect()
+    }
+
+    /// Arc length within roughly `tolerance`, by adaptive subdivision until the
+    /// chord and the control polygon agree.
+    fn arc_length(&self, tolerance: f32) -> f32 {
+        arc_length(self, tolerance, 0)
+    }
+
+    /// Returns the parameter and position of the point on the curve nearest to `point`.
+    fn nearest(&self, point: CanvasPoint) -> (f32, CanvasPoint) {
+        let dist = |t: f32| self.eval(t).euclid_dist(point);
+
+        let mut t = (0..=NEAREST_SAMPLES)
+            .map(|i| i as f32 / NEAREST_SAMPLES as f32)
+            .min_by(|&a, &b| dist(a).total_cmp(&dist(b)))
+            .unwrap_or(0.0);
+
+        // Newton's method on f(t) = (B(t) - p) · B'(t)
+        for _ in 0..NEAREST_NEWTON_ITERATIONS {
+            let diff = self.eval(t) - point;
+            let d1 = self.derivative(t);
+            let denom = d1.dot(d1) + diff.dot(self.second_derivative(t));
+
+            if denom.abs() <= f32::EPSILON {
+                break;
+            }
+
+            t = (t - diff.dot(d1) / denom).clamp(0.0, 1.0);
+        }
+
+        (t, self.eval(t))
+    }
+}
+
+fn arc_length<B: Bezier>(curve: &B, tolerance: f32, depth: usize) -> f32 {
+    let chord = curve.from().euclid_dist(curve.to());
+    let hull = curve.hull_length();
+
+    if hull - chord <= tolerance || depth >= MAX_ARC_LENGTH_DEPTH {
+        return (chord + hull) / 2.0;
+    }
+
+    let (left, right) = curve.split(0.5);
+
+    arc_length(&left, tolerance / 2.0, depth + 1) + arc_length(&right, tolerance / 2.0, depth + 1)
+}
+
+/// Roots of `a t² + b t + c` that lie strictly inside `(0, 1)`.
+fn unit_quadratic_roots(a: f32, b: f32, c: f32) -> impl Iterator<Item = f32> {
+    let roots = if a == 0.0 {
+        [(b != 0.0).then(|| -c / b), None]
+    } else {
+        let disc = b * b - 4.0 * a * c;
+
+        if disc < 0.0 {
+            [None, None]
+        } else {
+            // avoid cancellation by never subtracting nearly equal magnitudes
+            let q = -0.5 * (b + b.signum() * disc.sqrt());
+            [Some(q / a), (q != 0.0).then(|| c / q)]
+        }
+    };
+
+    roots.into_iter().flatten().filter(|t| *t > 0.0 && *t < 1.0)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct QuadraticBezier {
+    pub from: CanvasPoint,
+    pub ctrl: CanvasPoint,
+    pub to: CanvasPoint,
+}
+
+impl QuadraticBezier {
+    pub fn new(from: CanvasPoint, ctrl: CanvasPoint, to: CanvasPoint) -> Self {
+        Self { from, ctrl, to }
+    }
+
+    /// The exact cubic representation of this curve.
+    pub fn elevate(&self) -> CubicBezier {
+        CubicBezier::new(
+            self.from,
+            self.from.lerp(self.ctrl, 2.0 / 3.0),
+            self.to.lerp(self.ctrl, 2.0 / 3.0),
+            self.to,
+        )
+    }
+}
+
+impl Bezier for QuadraticBezier {
+    fn from(&self) -> CanvasPoint {
+        self.from
+    }
+
+    fn to(&self) -> CanvasPoint {
+        self.to
+    }
+
+    fn eval(&self, t: f32) -> CanvasPoint {
+        let mt = 1.0 - t;
+
+        self.from * CanvasPoint::splat(mt * mt)
+            + self.ctrl * CanvasPoint::splat(2.0 * mt * t)
+            + self.to * CanvasPoint::splat(t * t)
+    }
+
+    fn derivative(&self, t: f32) -> CanvasPoint {
+        ((self.ctrl - self.from).lerp(self.to - self.ctrl, t)) * CanvasPoint::splat(2.0)
+    }
+
+    fn second_derivative(&self, _t: f32) -> CanvasPoint {
+        (self.from - self.ctrl - self.ctrl + self.to) * CanvasPoint::splat(2.0)
+    }
+
+    fn split(&self, t: f32) -> (Self, Self) {
+        let p01 = self.from.lerp(self.ctrl, t);
+        let p12 = self.ctrl.lerp(self.to, t);
+        let p012 = p01.lerp(p12, t);
+
+        (
+            Self::new(self.from, p01, p012),
+            Self::new(p012, p12, self.to),
+        )
+    }
+
+    fn bounds(&self) -> Aabb {
+        let endpoints = Aabb::from_corners(self.from, self.to);
+
+        // B'(t) = 0 at t = (p0 - p1) / (p0 - 2p1 + p2), solved for both axes at once
+        let num = self.from - self.ctrl;
+        let denom = self.from - self.ctrl - self.ctrl + self.to;
+        let (tx, ty) = (num / denom).coord();
+
+        [tx, ty]
+            .into_iter()
+            .filter(|t| *t > 0.0 && *t < 1.0)
+            .fold(endpoints, |aabb, t| {
+                let p = self.eval(t);
+                Aabb::new(aabb.min.min(p), aabb.max.max(p))
+            })
+    }
+
+    fn flatten_segments(&self, tolerance: f32) -> usize {
+        // the chord error of a uniform step is at most |B''| / (8 n²)
+        let dd = self.second_derivative(0.0).length();
+
+        ((dd / (8.0 * tolerance.max(f32::MIN_POSITIVE)))
+            .sqrt()
+            .ceil() as usize)
+            .clamp(1, MAX_FLATTEN_SEGMENTS)
+    }
+
+    fn hull_length(&self) -> f32 {
+        self.from.euclid_dist(self.ctrl) + self.ctrl.euclid_dist(self.to)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CubicBezier {
+    pub from: CanvasPoint,
+    pub ctrl1: CanvasPoint,
+    pub ctrl2: CanvasPoint,
+    pub to: CanvasPoint,
+}
+
+impl CubicBezier {
+    pub fn new(from: CanvasPoint, ctrl1: CanvasPoint, ctrl2: CanvasPoint, to: CanvasPoint) -> Self {
+        Self {
+            from,
+            ctrl1,
+            ctrl2,
+            to,
+        }
+    }
+
+    /// A straight line from `from` to `to` expressed as a cubic.
+    pub fn line(from: CanvasPoint, to: CanvasPoint) -> Self {
+        Self::new(from, from.lerp(to, 1.0 / 3.0), from.lerp(to, 2.0 / 3.0), to)
+    }
+
+    /// The same curve traversed from `to` back to `from`.
+    pub fn reverse(&self) -> Self {
+        Self::new(self.to, self.ctrl2, self.ctrl1, self.from)
+    }
+}
+
+impl Bezier for CubicBezier {
+    fn from(&self) -> CanvasPoint {
+        self.from
+    }
+
+    fn to(&self) -> CanvasPoint {
+        self.to
+    }
+
+    fn eval(&self, t: f32) -> CanvasPoint {
+        let mt = 1.0 - t;
+
+        self.from * CanvasPoint::splat(mt * mt * mt)
+            + self.ctrl1 * CanvasPoint::splat(3.0 * mt * mt * t)
+            + self.ctrl2 * CanvasPoint::splat(3.0 * mt * t * t)
+            + self.to * CanvasPoint::splat(t * t * t)
+    }
+
+    fn derivative(&self, t: f32) -> CanvasPoint {
+        QuadraticBezier::new(
+            self.ctrl1 - self.from,
+            self.ctrl2 - self.ctrl1,
+            self.to - self.ctrl2,
+        )
+        .eval(t)
+            * CanvasPoint::splat(3.0)
+    }
+
+    fn second_derivative(&self, t: f32) -> CanvasPoint {
+        let a = self.from - self.ctrl1 - self.ctrl1 + self.ctrl2;
+        let b = self.ctrl1 - self.ctrl2 - self.ctrl2 + self.to;
+
+        a.lerp(b, t) * CanvasPoint::splat(6.0)
+    }
+
+    fn split(&self, t: f32) -> (Self, Self) {
+        let p01 = self.from.lerp(self.ctrl1, t);
+        let p12 = self.ctrl1.lerp(self.ctrl2, t);
+        let p23 = self.ctrl2.lerp(self.to, t);
+        let p012 = p01.lerp(p12, t);
+        let p123 = p12.lerp(p23, t);
+        let p0123 = p012.lerp(p123, t);
+
+        (
+            Self::new(self.from, p01, p012, p0123),
+            Self::new(p0123, p123, p23, self.to),
+        )
+    }
+
+    fn bounds(&self) -> Aabb {
+        let endpoints = Aabb::from_corners(self.from, self.to);
+
+        // B'(t) / 3 = a t² + b t + c, with the coefficients for both axes in one vector
+        let three = CanvasPoint::splat(3.0);
+        let a = self.to - self.from + (self.ctrl1 - self.ctrl2) * three;
+        let b = (self.from - self.ctrl1 - self.ctrl1 + self.ctrl2) * CanvasPoint::splat(2.0);
+        let c = self.ctrl1 - self.from;
+
+        let (ax, ay) = a.coord();
+        let (bx, by) = b.coord();
+        let (cx, cy) = c.coord();
+
+        unit_quadratic_roots(ax, bx, cx)
+            .chain(unit_quadratic_roots(ay, by, cy))
+            .fold(endpoints, |aabb, t| {
+                let p = self.eval(t);
+                Aabb::new(aabb.min.min(p), aabb.max.max(p))
+            })
+    }
+
+    fn flatten_segments(&self, tolerance: f32) -> usize {
+        // |B''| <= 6 max(|p0 - 2p1 + p2|, |p1 - 2p2 + p3|), and the chord error of a
+        // uniform step is at most |B''| / (8 n²)
+        let d1 = (self.from - self.ctrl1 - self.ctrl1 + self.ctrl2).length();
+        let d2 = (self.ctrl1 - self.ctrl2 - self.ctrl2 + self.to).length();
+        let dd = 6.0 * d1.max(d2);
+
+        ((dd / (8.0 * tolerance.max(f32::MIN_POSITIVE)))
+            .sqrt()
+            .ceil() as usize)
+            .clamp(1, MAX_FLATTEN_SEGMENTS)
+    }
+
+    fn hull_length(&self) -> f32 {
+        self.from.euclid_dist(self.ctrl1)
+            + self.ctrl1.euclid_dist(self.ctrl2)
+            + self.ctrl2.euclid_dist(self.to)
+    }
+}
+
+/// Maximum number of Newton reparameterization passes before a fit is split.
+const FIT_MAX_REPARAMETERIZATIONS: usize = 4;
+
+/// Fits a piecewise cubic spline to `points` with Schneider's algorithm, from
+/// "An Algorithm for Automatically Fitting Digitized Curves" (Graphics Gems, 1990).
+///
+/// Every input point ends up within `tolerance` of the returned curves, which join
+/// with matching tangents. Returns no curves for fewer than two distinct points.
+pub fn fit_cubic(points: &[CanvasPoint], tolerance: f32) -> Vec<CubicBezier> {
+    let mut points = points.to_vec();
+    points.dedup();
+
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    let last = points.len() - 1;
+    let start_tangent = (points[1] - points[0]).normalize();
+    let end_tangent = (points[last - 1] - points[last]).normalize();
+
+    let mut curves = Vec::new();
+    fit_cubic_range(
+        &points,
+        start_tangent,
+        end_tangent,
+        tolerance * tolerance,
+        &mut curves,
+    );
+
+    curves
+}
+
+fn fit_cubic_range(
+    points: &[CanvasPoint],
+    start_tangent: CanvasPoint,
+    end_tangent: CanvasPoint,
+    error_sq: f32,
+    curves: &mut Vec<CubicBezier>,
+) {
+    let last = points.len() - 1;
+
+    if points.len() == 2 {
+        let dist = CanvasPoint::splat(points[0].euclid_dist(points[1]) / 3.0);
+
+        curves.push(CubicBezier::new(
+            points[0],
+            points[0] + start_tangent * dist,
+            points[1] + end_tangent * dist,
+            points[1],
+        ));
+        return;
+    }
+
+    let mut u = chord_length_parameterize(points);
+    let mut curve = generate_bezier(points, &u, start_tangent, end_tangent);
+    let (mut max_error, mut split) = fit_error(points, &curve, &u);
+
+    if max_error < error_sq {
+        curves.push(curve);
+        return;
+    }
+
+    // close misses are usually a poor parameterization rather than a poor shape
+    if max_error < error_sq * 4.0 {
+        for _ in 0..FIT_MAX_REPARAMETERIZATIONS {
+            u = reparameterize(points, &u, &curve);
+            curve = generate_bezier(points, &u, start_tangent, end_tangent);
+            (max_error, split) = fit_error(points, &curve, &u);
+
+            if max_error < error_sq {
+                curves.push(curve);
+                return;
+            }
+        }
+    }
+
+    let center_tangent = (points[split - 1] - points[split + 1]).normalize();
+    let center_tangent = if center_tangent.length().is_nan() {
+        // the neighbours coincide, so fall back to the direction into the split point
+        (points[split - 1] - points[split]).normalize()
+    } else {
+        center_tangent
+    };
+
+    fit_cubic_range(
+        &points[..=split],
+        start_tangent,
+        center_tangent,
+        error_sq,
+        curves,
+    );
+    fit_cubic_range(
+        &points[split..=last],
+        -center_tangent,
+        end_tangent,
+        error_sq,
+        curves,
+    );
+}
+
+fn chord_length_parameterize(points: &[CanvasPoint]) -> Vec<f32> {
+    let mut u = Vec::with_capacity(points.len());
+    u.push(0.0);
+
+    for w in points.windows(2) {
+        let prev = *u.last().unwrap_or(&0.0);
+        u.push(prev + w[0].euclid_dist(w[1]));
+    }
+
+    let total = *u.last().unwrap_or(&0.0);
+    u.iter_mut().for_each(|t| *t /= total);
+
+    u
+}
+
+/// Least-squares fit of the two tangent magnitudes, with the endpoints pinned.
+fn generate_bezier(
+    points: &[CanvasPoint],
+    u: &[f32],
+    start_tangent: CanvasPoint,
+    end_tangent: CanvasPoint,
+) -> CubicBezier {
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    let (mut c00, mut c01, mut c11, mut x0, mut x1) = (0.0, 0.0, 0.0, 0.0, 0.0);
+
+    for (&p, &t) in points.iter().zip(u) {
+        let mt = 1.0 - t;
+        let (b0, b1, b2, b3) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+
+        let a0 = start_tangent * CanvasPoint::splat(b1);
+        let a1 = end_tangent * CanvasPoint::splat(b2);
+        let rest = p - (first * CanvasPoint::splat(b0 + b1) + last * CanvasPoint::splat(b2 + b3));
+
+        c00 += a0.dot(a0);
+        c01 += a0.dot(a1);
+        c11 += a1.dot(a1);
+        x0 += a0.dot(rest);
+        x1 += a1.dot(rest);
+    }
+
+    let det = c00 * c11 - c01 * c01;
+    let (alpha_l, alpha_r) = if det == 0.0 {
+        (0.0, 0.0)
+    } else {
+        ((x0 * c11 - x1 * c01) / det, (c00 * x1 - c01 * x0) / det)
+    };
+
+    // negative or vanishing handles mean the system is ill conditioned, so fall back
+    // to the Wu/Barsky heuristic of a third of the chord
+    let chord = first.euclid_dist(last);
+    let epsilon = 1e-6 * chord;
+    let (alpha_l, alpha_r) = if alpha_l < epsilon || alpha_r < epsilon {
+        (chord / 3.0, chord / 3.0)
+    } else {
+        (alpha_l, alpha_r)
+    };
+
+    CubicBezier::new(
+        first,
+        first + start_tangent * CanvasPoint::splat(alpha_l),
+        last + end_tangent * CanvasPoint::splat(alpha_r),
+        last,
+    )
+}
+
+/// The largest squared distance from a point to its parameter on the curve, and the
+/// index of that point.
+fn fit_error(points: &[CanvasPoint], curve: &CubicBezier, u: &[f32]) -> (f32, usize) {
+    let last = points.len() - 1;
+
+    (1..last)
+        .map(|i| {
+            let diff = curve.eval(u[i]) - points[i];
+            (diff.dot(diff), i)
+        })
+        .fold((0.0, points.len() / 2), |worst, candidate| {
+            if candidate.0 > worst.0 {
+                candidate
+            } else {
+                worst
+            }
+        })
+}
+
+/// One Newton step per point towards the parameter of its nearest curve point.
+fn reparameterize(points: &[CanvasPoint], u: &[f32], curve: &CubicBezier) -> Vec<f32> {
+    points
+        .iter()
+        .zip(u)
+        .map(|(&p, &t)| {
+            let diff = curve.eval(t) - p;
+            let d1 = curve.derivative(t);
+            let denom = d1.dot(d1) + diff.dot(curve.second_derivative(t));
+
+            if denom == 0.0 {
+                t
+            } else {
+                t - diff.dot(d1) / denom
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    /// Quarter of the unit circle from (1, 0) to (0, 1).
+    fn quarter_circle() -> CubicBezier {
+        let k = 0.552_284_8;
+
+        CubicBezier::new(
+            CanvasPoint::new(1.0, 0.0),
+            CanvasPoint::new(1.0, k),
+            CanvasPoint::new(k, 1.0),
+            CanvasPoint::new(0.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn eval_endpoints() {
+        let c = quarter_circle();
+
+        assert_eq!(c.eval(0.0), c.from);
+        assert_eq!(c.eval(1.0), c.to);
+        assert!((c.eval(0.5).length() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn derivative() {
+        let c = CubicBezier::line(CanvasPoint::new(0.0, 0.0), CanvasPoint::new(3.0, 6.0));
+
+        assert_eq!(c.derivative(0.3), CanvasPoint::new(3.0, 6.0));
+        assert_eq!(c.second_derivative(0.3), CanvasPoint::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn split() {
+        let c = quarter_circle();
+        let (left, right) = c.split(0.25);
+
+        assert_eq!(left.to, right.from);
+        assert_eq!(left.to, c.eval(0.25));
+        assert_eq!(left.eval(0.5), c.eval(0.125));
+        assert_eq!(right.eval(0.5), c.eval(0.625));
+    }
+
+    #[test]
+    fn quadratic_split() {
+        let q = QuadraticBezier::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(1.0, 2.0),
+            CanvasPoint::new(2.0, 0.0),
+        );
+        let (left, right) = q.split(0.5);
+
+        assert_eq!(left.to, CanvasPoint::new(1.0, 1.0));
+        assert_eq!(right.eval(0.5), q.eval(0.75));
+    }
+
+    #[test]
+    fn elevate() {
+        let q = QuadraticBezier::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(1.0, 2.0),
+            CanvasPoint::new(2.0, 0.0),
+        );
+        let c = q.elevate();
+
+        for t in [0.0, 0.2, 0.5, 0.9, 1.0] {
+            assert!(c.eval(t).euclid_dist(q.eval(t)) < 1e-5);
+        }
+    }
+
+    #[test]
+    fn bounds() {
+        let q = QuadraticBezier::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(1.0, 2.0),
+            CanvasPoint::new(2.0, 0.0),
+        );
+        assert_eq!(
+            q.bounds(),
+            Aabb::new(CanvasPoint::new(0.0, 0.0), CanvasPoint::new(2.0, 1.0))
+        );
+
+        // an S-curve whose control points overshoot the extrema
+        let c = CubicBezier::new(
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(0.0, 4.0),
+            CanvasPoint::new(4.0, -4.0),
+            CanvasPoint::new(4.0, 0.0),
+        );
+        let b = c.bounds();
+        let samples = (0..=1000).map(|i| c.eval(i as f32 / 1000.0));
+
+        assert_eq!(b.min.one(), 0.0);
+        assert_eq!(b.max.one(), 4.0);
+        assert!(b.max.two() < 4.0);
+        assert!(samples.into_iter().all(|p| b.expand(1e-4).contains(p)));
+    }
+
+    #[test]
+    fn arc_length() {
+        let line = CubicBezier::line(CanvasPoint::new(0.0, 0.0), CanvasPoint::new(3.0, 4.0));
+        assert!((line.arc_length(1e-3) - 5.0).abs() < 1e-4);
+
+        assert!((quarter_circle().arc_length(1e-4) - FRAC_PI_2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn nearest() {
+        let c = quarter_circle();
+        let (t, p) = c.nearest(CanvasPoint::new(2.0, 2.0));
+
+        assert!((t - 0.5).abs() < 1e-3);
+        assert!(p.euclid_dist(CanvasPoint::splat(std::f32::consts::FRAC_1_SQRT_2)) < 1e-3);
+
+        let (t, p) = c.nearest(CanvasPoint::new(5.0, -3.0));
+        assert_eq!(t, 0.0);
+        assert_eq!(p, c.from);
+    }
+
+    #[test]
+    fn fit_line() {
+        let points: Vec<CanvasPoint> = (0..=10)
+            .map(|i| CanvasPoint::new(i as f32, 2.0 * i as f32))
+            .collect();
+        let curves = fit_cubic(&points, 0.1);
+
+        assert_eq!(curves.len(), 1);
+        assert_eq!(curves[0].from, points[0]);
+        assert_eq!(curves[0].to, points[10]);
+    }
+
+    #[test]
+    fn fit_within_tolerance() {
+        // a circle arc followed by a sharp turn into a straight line
+        let points: Vec<CanvasPoint> = (0..=40)
+            .map(|i| {
+                let a = i as f32 / 40.0 * std::f32::consts::PI;
+                CanvasPoint::new(50.0 * a.cos(), 50.0 * a.sin())
+            })
+            .chain((1..=20).map(|i| CanvasPoint::new(-50.0 + 3.0 * i as f32, 0.0)))
+            .collect();
+        let tolerance = 0.5;
+        let curves = fit_cubic(&points, tolerance);
+
+        assert!(curves.len() < 10);
+        assert_eq!(curves[0].from, points[0]);
+        assert_eq!(curves[curves.len() - 1].to, points[points.len() - 1]);
+        for pair in curves.windows(2) {
+            assert_eq!(pair[0].to, pair[1].from);
+        }
+
+        let flattened: Vec<CanvasPoint> = curves.iter().flat_map(|c| c.flatten(0.01)).collect();
+        for p in points {
+            assert!(crate::polyline::distance(&flattened, p) <= tolerance + 0.02);
+        }
+    }
+
+    #[test]
+    fn fit_degenerate() {
+        let p = CanvasPoint::new(1.0, 1.0);
+
+        assert!(fit_cubic(&[], 1.0).is_empty());
+        assert!(fit_cubic(&[p, p, p], 1.0).is_empty());
+        assert_eq!(
+            fit_cubic(&[p, p + CanvasPoint::new(3.0, 0.0)], 1.0).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn flatten_within_tolerance() {
+        let c = quarter_circle().split(0.5).0;
+        let tolerance = 1e-3;
+        let points = c.flatten(tolerance);
+        let n = points.len() - 1;
+
+        assert_eq!(points[0], c.from);
+        assert_eq!(points[n], c.to);
+
+        for (i, segment) in points.windows(2).enumerate() {
+            let mid_t = (i as f32 + 0.5) / n as f32;
+            assert!(c.eval(mid_t).euclid_dist(segment[0].midpoint(segment[1])) <= tolerance);
+        }
+
+        // no tolerance at all still makes a finite polyline
+        for tolerance in [0.0, -1.0] {
+            let points = c.flatten(tolerance);
+            assert_eq!(points.len(), MAX_FLATTEN_SEGMENTS + 1);
+            assert_eq!(points.last(), Some(&c.to));
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Boolean operations on regions bounded by closed polygons.
+//!
+//! A region is a list of contours filled with the nonzero rule, the SVG default. Results
+//! wind counter-clockwise around filled areas and clockwise around holes (y-up, as in
+//! [`crate::polygon`]), so they can be fed straight back in.
+//!
+//! Both regions are cut where their edges cross, and each piece is kept or dropped by
+//! whether it runs inside or outside the other region. Pieces shared by both regions are
+//! kept once when they face the same way.
+
+use crate::{polygon, CanvasPoint, Segment};
+
+/// Points that agree to within this relative tolerance, as in
+/// [`CanvasPoint::relative_eq`], are treated as the same point.
+const TOLERANCE: f32 = 1e-6;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operation {
+    Union,
+    Intersection,
+    /// The first region with the second cut away.
+    Difference,
+    Xor,
+}
+
+pub fn union(a: &[Vec<CanvasPoint>], b: &[Vec<CanvasPoint>]) -> Vec<Vec<CanvasPoint>> {
+    apply(Operation::Union, a, b)
+}
+
+pub fn intersection(a: &[Vec<CanvasPoint>], b: &[Vec<CanvasPoint>]) -> Vec<Vec<CanvasPoint>> {
+    apply(Operation::Intersection, a, b)
+}
+
+pub fn difference(a: &[Vec<CanvasPoint>], b: &[Vec<CanvasPoint>]) -> Vec<Vec<CanvasPoint>> {
+    apply(Operation::Difference, a, b)
+}
+
+pub fn xor(a: &[Vec<CanvasPoint>], b: &[Vec<CanvasPoint>]) -> Vec<Vec<CanvasPoint>> {
+    apply(Operation::Xor, a, b)
+}
+
+/// Where a piece of one region's boundary runs relative to the other region.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Side {
+    Inside,
+    Outside,
+    /// Along the other boundary, facing the same way.
+    Same,
+    /// Along the other boundary, facing the opposite way.
+    Opposite,
+}
+
+pub fn apply(
+    operation: Operation,
+    a: &[Vec<CanvasPoint>],
+    b: &[Vec<CanvasPoint>],
+) -> Vec<Vec<CanvasPoint>> {
+    if operation == Operation::Xor {
+        let mut result = apply(Operation::Difference, a, b);
+        result.extend(apply(Operation::Difference, b, a));
+
+        return result;
+    }
+
+    let (a, b) = (orient(a), orient(b));
+    let (a_edges, b_edges) = split_edges(&edges(&a), &edges(&b));
+
+    let a_kept = a_edges.iter().filter(|e| {
+        let side = classify(**e, &b);
+
+        match operation {
+            Operation::Union => matches!(side, Side::Outside | Side::Same),
+            Operation::Intersection => matches!(side, Side::Inside | Side::Same),
+            _ => matches!(side, Side::Outside | Side::Opposite),
+        }
+    });
+
+    let b_kept = b_edges.iter().filter_map(|e| {
+        let side = classify(*e, &a);
+
+        match operation {
+            Operation::Union => (side == Side::Outside).then_some(*e),
+            Operation::Intersection => (side == Side::Inside).then_some(*e),
+            // the cut away part bounds the result from the other side
+            _ => (side == Side::Inside).then(|| Segment::new(e.to, e.from)),
+        }
+    });
+
+    link(a_kept.copied().chain(b_kept).collect())
+}
+
+/// Drops repeated points and degenerate contours, and reverses the region if it winds
+/// clockwise overall.
+fn orient(region: &[Vec<CanvasPoint>]) -> Vec<Vec<CanvasPoint>> {
+    let mut region: Vec<Vec<CanvasPoint>> = region
+        .iter()
+        .map(|contour| {
+            let mut contour = contour.clone();
+            contour.dedup_by(|p, q| p.relative_eq(*q, TOLERANCE));
+
+            while contour.len() > 1 && contour[0].relative_eq(contour[contour.len() - 1], TOLERANCE)
+            {
+                contour.pop();
+            }
+
+            contour
+        })
+        .filter(|contour| contour.len() >= 3)
+        .collect();
+
+    let area: f32 = region.iter().map(|c| polygon::signed_area(c)).sum();
+    if area < 0.0 {
+        region.iter_mut().for_each(|c| c.reverse());
+    }
+
+    region
+}
+
+fn edges(region: &[Vec<CanvasPoint>]) -> Vec<Segment> {
+    region.iter().flat_map(|c| polygon::edges(c)).collect()
+}
+
+fn winding_number(region: &[Vec<CanvasPoint>], point: CanvasPoint) -> i32 {
+    region
+        .iter()
+        .map(|c| polygon::winding_number(c, point))
+        .sum()
+}
+
+fn classify(edge: Segment, region: &[Vec<CanvasPoint>]) -> Side {
+    let mid = edge.from.midpoint(edge.to);
+
+    let along = region
+        .iter()
+        .flat_map(|c| polygon::edges(c))
+        .find(|e| e.closest_point(mid).relative_eq(mid, TOLERANCE));
+
+    match along {
+        Some(e) if e.direction().dot(edge.direction()) > 0.0 => Side::Same,
+        Some(_) => Side::Opposite,
+        None if winding_number(region, mid) != 0 => Side::Inside,
+        None => Side::Outside,
+    }
+}
+
+/// Where two edges touch: one point where they cross, or the ends of the stretch they
+/// share when they overlap.
+fn crossings(a: Segment, b: Segment) -> Vec<CanvasPoint> {
+    let sides = [a.side(b.from), a.side(b.to), b.side(a.from), b.side(a.to)];
+
+    if sides == [0; 4] {
+        // collinear, so only an overlap can cut either edge, at the ends that lie on the
+        // other edge
+        let on = |edge: Segment, p: CanvasPoint| edge.intersects(Segment::new(p, p));
+        let on_a = [b.from, b.to].into_iter().filter(|&p| on(a, p));
+        let on_b = [a.from, a.to].into_iter().filter(|&p| on(b, p));
+
+        return on_a.chain(on_b).collect();
+    }
+
+    a.intersection(b)
+        .map(|p| {
+            // snap to a nearby vertex so both edges are cut at exactly the same point
+            [a.from, a.to, b.from, b.to]
+                .into_iter()
+                .find(|v| v.relative_eq(p, TOLERANCE))
+                .unwrap_or(p)
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Cuts every edge of `a` and `b` where it touches an edge of the other.
+fn split_edges(a: &[Segment], b: &[Segment]) -> (Vec<Segment>, Vec<Segment>) {
+    let mut a_cuts = vec![Vec::new(); a.len()];
+    let mut b_cuts = vec![Vec::new(); b.len()];
+
+    for (i, ea) in a.iter().enumerate() {
+        let bounds = ea.bounds();
+
+        for (j, eb) in b.iter().enumerate() {
+            if !bounds.intersects(eb.bounds()) {
+                continue;
+            }
+
+            for p in crossings(*ea, *eb) {
+                a_cuts[i].push(p);
+                b_cuts[j].push(p);
+            }
+        }
+    }
+
+    (cut(a, a_cuts), cut(b, b_cuts))
+}
+
+fn cut(edges: &[Segment], cuts: Vec<Vec<CanvasPoint>>) -> Vec<Segment> {
+    edges
+        .iter()
+        .zip(cuts)
+        .flat_map(|(edge, mut cuts)| {
+            // cuts at the ends would leave pieces with no length
+            cuts.retain(|p| !p.relative_eq(edge.from, TOLERANCE));
+            cuts.retain(|p| !p.relative_eq(edge.to, TOLERANCE));
+            cuts.sort_by(|p, q| edge.project(*p).total_cmp(&edge.project(*q)));
+            cuts.dedup_by(|p, q| p.relative_eq(*q, TOLERANCE));
+
+            let points: Vec<CanvasPoint> = std::iter::once(edge.from)
+                .chain(cuts)
+                .chain(std::iter::once(edge.to))
+                .collect();
+
+            points
+                .windows(2)
+                .map(|p| Segment::new(p[0], p[1]))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Chains directed edges end to start into closed contours.
+fn link(mut edges: Vec<Segment>) -> Vec<Vec<CanvasPoint>> {
+    let mut contours = Vec::new();
+
+    while let Some(first) = edges.pop() {
+        let mut contour = vec![first.from];
+        let mut end = first.to;
+
+        while !end.relative_eq(first.from, TOLERANCE) {
+            let next = edges
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.from.relative_eq(end, TOLERANCE))
+                .map(|(i, e)| (i, e.from.euclid_dist(end)))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+
+            let Some((i, _)) = next else {
+                break;
+            };
+
+            let edge = edges.swap_remove(i);
+            contour.push(edge.from);
+            end = edge.to;
+        }
+
+        if contour.len() >= 3 {
+            contours.push(contour);
+        }
+    }
+
+    contours
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<Vec<CanvasPoint>> {
+        vec![vec![
+            CanvasPoint::new(x, y),
+            CanvasPoint::new(x + size, y),
+            CanvasPoint::new(x + size, y + size),
+            CanvasPoint::new(x, y + size),
+        ]]
+    }
+
+    fn area(region: &[Vec<CanvasPoint>]) -> f32 {
+        region.iter().map(|c| polygon::signed_area(c)).sum()
+    }
+
+    #[test]
+    fn overlapping_squares() {
+        let (a, b) = (square(0.0, 0.0, 4.0), square(2.0, 2.0, 4.0));
+
+        assert_eq!(area(&union(&a, &b)), 28.0);
+        assert_eq!(area(&intersection(&a, &b)), 4.0);
+        assert_eq!(area(&difference(&a, &b)), 12.0);
+        assert_eq!(area(&difference(&b, &a)), 12.0);
+        assert_eq!(area(&xor(&a, &b)), 24.0);
+
+        assert_eq!(union(&a, &b).len(), 1);
+        assert!(!polygon::contains(
+            &intersection(&a, &b)[0],
+            CanvasPoint::new(1.0, 1.0)
+        ));
+    }
+
+    #[test]
+    fn small_regions() {
+        // the corners are far closer together than a canvas unit
+        let (a, b) = (square(0.0, 0.0, 1e-3), square(5e-4, 5e-4, 1e-3));
+
+        let union = union(&a, &b);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].len(), 8);
+        assert_eq!(intersection(&a, &b)[0].len(), 4);
+    }
+
+    #[test]
+    fn holes_and_disjoint_regions() {
+        let (outer, inner) = (square(0.0, 0.0, 6.0), square(2.0, 2.0, 2.0));
+
+        let ring = difference(&outer, &inner);
+        assert_eq!(ring.len(), 2);
+        assert_eq!(area(&ring), 32.0);
+        assert_eq!(winding_number(&ring, CanvasPoint::new(3.0, 3.0)), 0);
+        assert_eq!(winding_number(&ring, CanvasPoint::new(1.0, 1.0)), 1);
+
+        // notching the corner of the ring keeps the hole
+        let notched = difference(&ring, &square(5.0, 0.0, 1.0));
+        assert_eq!(notched.len(), 2);
+        assert_eq!(area(&notched), 31.0);
+
+        let apart = square(10.0, 10.0, 1.0);
+        assert_eq!(area(&union(&inner, &apart)), 5.0);
+        assert!(intersection(&inner, &apart).is_empty());
+        assert_eq!(area(&difference(&inner, &apart)), 4.0);
+    }
+
+    #[test]
+    fn shared_edges() {
+        let a = square(0.0, 0.0, 4.0);
+
+        // side by side, so the shared edge disappears
+        let merged = union(&a, &square(4.0, 0.0, 4.0));
+        assert_eq!(merged.len(), 1);
+        assert_eq!(area(&merged), 32.0);
+
+        // identical squares
+        assert_eq!(area(&union(&a, &a)), 16.0);
+        assert_eq!(area(&intersection(&a, &a)), 16.0);
+        assert!(difference(&a, &a).is_empty());
+
+        // clockwise input is normalised
+        let mut reversed = a.clone();
+        reversed[0].reverse();
+        assert_eq!(area(&intersection(&reversed, &square(2.0, 0.0, 4.0))), 8.0);
+    }
+
+    #[test]
+    fn flattened_circles() {
+        let circle = |cx: f32| {
+            vec![(0..64)
+                .map(|i| {
+                    let angle = i as f32 / 64.0 * std::f32::consts::TAU;
+                    CanvasPoint::new(cx + 10.0 * angle.cos(), 10.0 * angle.sin())
+                })
+                .collect::<Vec<_>>()]
+        };
+        let disc = area(&circle(0.0));
+
+        // cutting away the right half plane leaves half the disc
+        let half = difference(&circle(0.0), &square(0.0, -20.0, 40.0));
+        assert!((area(&half) - disc / 2.0).abs() < 1e-2);
+
+        let (a, b) = (circle(0.0), circle(10.0));
+        let lens = area(&intersection(&a, &b));
+        assert!(lens > 0.0);
+        assert!((area(&union(&a, &b)) - (2.0 * disc - lens)).abs() < 1e-2);
+        assert!((area(&xor(&a, &b)) - (2.0 * disc - 2.0 * lens)).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,270 @@
+use std::f32::consts::TAU;
+
+use crate::{polygon, Aabb, CanvasPoint, Segment, Transform};
+
+/// An ellipse with semi-axes `radii` along its own axes, turned by `rotation` radians about
+/// `center` (the same sense as [`Transform::rotate`]). A circle is the case of equal radii.
+///
+/// Like [`Aabb`], ellipses are closed, so touching counts as overlapping.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Ellipse {
+    pub center: CanvasPoint,
+    pub radii: CanvasPoint,
+    pub rotation: f32,
+}
+
+impl Ellipse {
+    pub fn new(center: CanvasPoint, radii: CanvasPoint, rotation: f32) -> Self {
+        Self {
+            center,
+            radii,
+            rotation,
+        }
+    }
+
+    pub fn circle(center: CanvasPoint, radius: f32) -> Self {
+        Self::new(center, CanvasPoint::splat(radius), 0.0)
+    }
+
+    /// The ellipse inscribed in `aabb`.
+    pub fn from_aabb(aabb: Aabb) -> Self {
+        Self::new(
+            aabb.center(),
+            aabb.width_height() / CanvasPoint::splat(2.0),
+            0.0,
+        )
+    }
+
+    /// The ellipse's own x and y axes as unit vectors.
+    pub fn axes(&self) -> [CanvasPoint; 2] {
+        let (sin, cos) = self.rotation.sin_cos();
+
+        [CanvasPoint::new(cos, sin), CanvasPoint::new(-sin, cos)]
+    }
+
+    /// The transform from the unit circle to this ellipse.
+    pub fn transform(&self) -> Transform {
+        Transform::scale(self.radii)
+            .then(Transform::rotate(self.rotation))
+            .then(Transform::translate(self.center))
+    }
+
+    /// `point` in the ellipse's own frame, scaled so the ellipse is the unit circle. A
+    /// radius of zero is treated as the smallest positive one, so the frame stays finite.
+    pub fn to_unit(&self, point: CanvasPoint) -> CanvasPoint {
+        let [u, v] = self.axes();
+        let d = point - self.center;
+        let radii = self.radii.abs().max(CanvasPoint::splat(f32::MIN_POSITIVE));
+
+        CanvasPoint::new(d.dot(u), d.dot(v)) / radii
+    }
+
+    /// The point at `angle` radians round the ellipse from the end of its x axis.
+    pub fn point_at(&self, angle: f32) -> CanvasPoint {
+        let (sin, cos) = angle.sin_cos();
+
+        self.transform().apply(CanvasPoint::new(cos, sin))
+    }
+
+    pub fn contains(&self, point: CanvasPoint) -> bool {
+        let unit = self.to_unit(point);
+
+        unit.dot(unit) <= 1.0
+    }
+
+    /// The smallest axis-aligned box around the ellipse.
+    pub fn bounds(&self) -> Aabb {
+        let [u, v] = self.axes();
+        let (rx, ry) = self.radii.abs().coord();
+        // each coordinate is a sinusoid in the angle round the ellipse, with the amplitude
+        // of its two axes' components combined
+        let extent =
+            (u * u * CanvasPoint::splat(rx * rx) + v * v * CanvasPoint::splat(ry * ry)).sqrt();
+
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    /// Whether the ellipse and `aabb` overlap. In the ellipse's unit frame the box becomes
+    /// a parallelogram, which overlaps the unit circle exactly when it holds the centre or
+    /// one of its edges comes within a radius of it. An ellipse with a zero radius has no
+    /// unit frame, and is tested as the segment, or point, that it collapses to.
+    pub fn intersects_aabb(&self, aabb: Aabb) -> bool {
+        if !self.bounds().intersects(aabb) {
+            return false;
+        }
+
+        let (rx, ry) = self.radii.abs().coord();
+        if rx == 0.0 || ry == 0.0 {
+            let [u, v] = self.axes();
+            let half = u * CanvasPoint::splat(rx) + v * CanvasPoint::splat(ry);
+
+            return Segment::new(self.center - half, self.center + half)
+                .clip(aabb)
+                .is_some();
+        }
+
+        let corners = [
+            aabb.min,
+            CanvasPoint::new(aabb.max.one(), aabb.min.two()),
+            aabb.max,
+            CanvasPoint::new(aabb.min.one(), aabb.max.two()),
+        ]
+        .map(|c| self.to_unit(c));
+        let origin = CanvasPoint::splat(0.0);
+
+        polygon::contains(&corners, origin)
+            || polygon::edges(&corners).any(|e: Segment| e.distance(origin) <= 1.0)
+    }
+
+    /// The image of the ellipse under `transform`, which is always an ellipse. Its axes
+    /// come from the singular value decomposition of the combined linear map.
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        let [u, v] = self.axes();
+        let (rx, ry) = self.radii.coord();
+        let (m00, m10) = transform.apply_vector(u * CanvasPoint::splat(rx)).coord();
+        let (m01, m11) = transform.apply_vector(v * CanvasPoint::splat(ry)).coord();
+
+        // M = R(φ) · diag(sx, sy) · R(θ), and R(θ) maps the unit circle onto itself
+        let (e, f) = ((m00 + m11) / 2.0, (m00 - m11) / 2.0);
+        let (g, h) = ((m10 + m01) / 2.0, (m10 - m01) / 2.0);
+        let (q, r) = (e.hypot(h), f.hypot(g));
+        let phi = (h.atan2(e) + g.atan2(f)) / 2.0;
+
+        Self::new(
+            transform.apply(self.center),
+            CanvasPoint::new(q + r, (q - r).abs()),
+            phi,
+        )
+    }
+
+    /// The largest angle a chord may span and stay within `tolerance` of the ellipse.
+    fn step(&self, tolerance: f32) -> f32 {
+        // a chord spanning the angle θ sags at most r (1 - cos(θ / 2)) below the arc,
+        // where r is the larger radius
+        let radius = self.radii.abs().one().max(self.radii.abs().two());
+
+        2.0 * (1.0 - tolerance / radius).clamp(-1.0, 1.0).acos()
+    }
+
+    /// The ellipse as a polygon whose edges stay within `tolerance` of it.
+    pub fn polygon(&self, tolerance: f32) -> Vec<CanvasPoint> {
+        let n = ((TAU / self.step(tolerance)).ceil() as usize).clamp(8, 1024);
+
+        (0..n)
+            .map(|i| self.point_at(i as f32 / n as f32 * TAU))
+            .collect()
+    }
+
+    /// The arc from `start` radians round by `sweep` radians, as in [`Ellipse::point_at`],
+    /// as a polyline through both ends whose edges stay within `tolerance` of it.
+    pub fn arc(&self, start: f32, sweep: f32, tolerance: f32) -> Vec<CanvasPoint> {
+        let n = ((sweep.abs() / self.step(tolerance)).ceil() as usize).clamp(4, 1024);
+
+        (0..=n)
+            .map(|i| self.point_at(start + i as f32 / n as f32 * sweep))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn p(x: f32, y: f32) -> CanvasPoint {
+        CanvasPoint::new(x, y)
+    }
+
+    /// 20 × 10 radii, turned a quarter so its long axis is vertical.
+    fn upright() -> Ellipse {
+        Ellipse::new(p(0.0, 0.0), p(20.0, 10.0), FRAC_PI_2)
+    }
+
+    #[test]
+    fn contains() {
+        let e = upright();
+
+        assert!(e.contains(p(0.0, 19.9)));
+        assert!(e.contains(p(9.9, 0.0)));
+        assert!(!e.contains(p(10.1, 0.0)));
+        // inside the bounds, outside the curve
+        assert!(!e.contains(p(9.0, 18.0)));
+    }
+
+    #[test]
+    fn bounds() {
+        let b = upright().bounds();
+
+        assert!(b.min.approx_eq(p(-10.0, -20.0), 1e-4));
+        assert!(b.max.approx_eq(p(10.0, 20.0), 1e-4));
+        assert_eq!(
+            Ellipse::from_aabb(Aabb::new(p(0.0, 0.0), p(40.0, 20.0))).bounds(),
+            Aabb::new(p(0.0, 0.0), p(40.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn intersects_aabb() {
+        let e = upright();
+
+        // the corner of the bounds misses the curve
+        assert!(!e.intersects_aabb(Aabb::new(p(8.0, 18.0), p(30.0, 30.0))));
+        assert!(e.intersects_aabb(Aabb::new(p(5.0, 15.0), p(30.0, 30.0))));
+        // a box inside, and a box around
+        assert!(e.intersects_aabb(Aabb::new(p(-1.0, -1.0), p(1.0, 1.0))));
+        assert!(e.intersects_aabb(Aabb::new(p(-50.0, -50.0), p(50.0, 50.0))));
+        // a thin box straight through, with every corner outside
+        assert!(e.intersects_aabb(Aabb::new(p(-50.0, -1.0), p(50.0, 1.0))));
+        assert!(!e.intersects_aabb(Aabb::new(p(11.0, -50.0), p(12.0, 50.0))));
+
+        // collapsed to a point, as left by a click without a drag, or to a segment
+        let dot = Ellipse::circle(p(5.0, 5.0), 0.0);
+        assert!(dot.intersects_aabb(Aabb::new(p(-5.0, -5.0), p(15.0, 15.0))));
+        assert!(!dot.intersects_aabb(Aabb::new(p(6.0, 6.0), p(15.0, 15.0))));
+        let flat = Ellipse::new(p(0.0, 0.0), p(0.0, 10.0), 0.0);
+        assert!(flat.intersects_aabb(Aabb::new(p(-1.0, 5.0), p(1.0, 20.0))));
+        assert!(!flat.intersects_aabb(Aabb::new(p(1.0, -5.0), p(2.0, 5.0))));
+    }
+
+    #[test]
+    fn transformed() {
+        let e = Ellipse::new(p(5.0, 5.0), p(20.0, 10.0), 0.3);
+        let t = Transform::skew(0.4, 0.0)
+            .then(Transform::scale(p(2.0, 0.5)))
+            .then(Transform::translate(p(3.0, -7.0)));
+        let image = e.transformed(&t);
+
+        // the image of every point on the curve is on the new curve
+        for i in 0..16 {
+            let q = t.apply(e.point_at(i as f32 / 16.0 * TAU));
+            assert!((image.to_unit(q).length() - 1.0).abs() < 1e-4);
+        }
+
+        // a rotation only turns it
+        let turned = e.transformed(&Transform::rotate(0.5));
+        assert!(turned.radii.approx_eq(e.radii, 1e-4));
+        assert!((turned.rotation - 0.8).abs() < 1e-5);
+    }
+
+    #[test]
+    fn polygon() {
+        let e = upright();
+        let outline = e.polygon(0.1);
+
+        assert!(outline.len() >= 8);
+        assert!(outline
+            .iter()
+            .all(|&q| (e.to_unit(q).length() - 1.0).abs() < 1e-4));
+        assert!(polygon::contains(&outline, p(0.0, 19.0)));
+
+        // the lower half, from the right end of the short axis round to the left end
+        let arc = e.arc(-FRAC_PI_2, std::f32::consts::PI, 0.1);
+        assert!(arc[0].approx_eq(p(10.0, 0.0), 1e-4));
+        assert!(arc.last().unwrap().approx_eq(p(-10.0, 0.0), 1e-4));
+        assert!(arc.iter().all(|q| q.two() >= -1e-4));
+    }
+}
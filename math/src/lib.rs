@@ -1,6 +1,31 @@
-#![cfg(target_arch = "wasm32")]
-#![feature(simd_wasm64)]
+#![cfg_attr(target_arch = "wasm32", feature(simd_wasm64))]
 
+pub use aabb::Aabb;
+pub use ellipse::Ellipse;
+pub use mask::BoolMask2;
+pub use obb::Obb;
 pub use point::CanvasPoint;
+pub use point_buffer::PointBuffer;
+pub use segment::Segment;
+pub use simd::v128;
+pub use transform::Transform;
+pub use world::WorldPoint;
 
+mod aabb;
+pub mod bezier;
+pub mod boolean;
+mod ellipse;
+mod mask;
+mod obb;
 mod point;
+mod point_buffer;
+pub mod polygon;
+pub mod polyline;
+pub mod predicates;
+pub mod route;
+mod segment;
+pub mod simd;
+pub mod simplify;
+pub mod stroke;
+mod transform;
+mod world;
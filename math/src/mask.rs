@@ -0,0 +1,116 @@
+use std::ops::{BitAnd, BitOr, Not};
+
+use crate::simd::{f32x4, i32x4_extract_lane, v128, v128_and, v128_bitselect, v128_not, v128_or};
+use crate::CanvasPoint;
+
+/// The outcome of a lane-wise comparison between two [`CanvasPoint`]s, one flag for x and
+/// one for y, as returned by `CanvasPoint::cmplt` and friends.
+///
+/// Lanes hold all ones or all zeros like a wasm comparison mask, so combining masks and
+/// selecting with them are single instructions. Only the x and y lanes are ever read.
+#[derive(Debug, Clone, Copy)]
+pub struct BoolMask2(pub v128);
+
+impl BoolMask2 {
+    pub fn new(x: bool, y: bool) -> Self {
+        let lane = |set: bool| f32::from_bits(if set { u32::MAX } else { 0 });
+        Self(f32x4(lane(x), lane(y), 0.0, 0.0))
+    }
+
+    #[inline(always)]
+    pub fn from_v128(mask: v128) -> Self {
+        Self(mask)
+    }
+
+    #[inline(always)]
+    pub fn x(self) -> bool {
+        i32x4_extract_lane::<0>(self.0) != 0
+    }
+
+    #[inline(always)]
+    pub fn y(self) -> bool {
+        i32x4_extract_lane::<1>(self.0) != 0
+    }
+
+    #[inline(always)]
+    pub fn all(self) -> bool {
+        self.x() && self.y()
+    }
+
+    #[inline(always)]
+    pub fn any(self) -> bool {
+        self.x() || self.y()
+    }
+
+    #[inline(always)]
+    pub fn none(self) -> bool {
+        !self.any()
+    }
+
+    /// Takes each lane from `if_true` where the mask is set and from `if_false` elsewhere.
+    #[inline(always)]
+    pub fn select(self, if_true: CanvasPoint, if_false: CanvasPoint) -> CanvasPoint {
+        CanvasPoint::from_v128(v128_bitselect(if_true.0, if_false.0, self.0))
+    }
+}
+
+impl BitAnd for BoolMask2 {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(v128_and(self.0, rhs.0))
+    }
+}
+
+impl BitOr for BoolMask2 {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(v128_or(self.0, rhs.0))
+    }
+}
+
+impl Not for BoolMask2 {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self(v128_not(self.0))
+    }
+}
+
+impl PartialEq for BoolMask2 {
+    fn eq(&self, other: &Self) -> bool {
+        self.x() == other.x() && self.y() == other.y()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    #[test]
+    fn flags() {
+        let mask = BoolMask2::new(true, false);
+
+        assert!(mask.x() && !mask.y());
+        assert!(mask.any() && !mask.all() && !mask.none());
+        assert_eq!(!mask, BoolMask2::new(false, true));
+        assert_eq!(mask & !mask, BoolMask2::new(false, false));
+        assert_eq!(mask | !mask, BoolMask2::new(true, true));
+    }
+
+    #[test]
+    fn select() {
+        let (a, b) = (CanvasPoint::new(1.0, 2.0), CanvasPoint::new(3.0, 0.0));
+
+        assert_eq!(
+            BoolMask2::new(true, false).select(a, b),
+            CanvasPoint::new(1.0, 0.0)
+        );
+        assert_eq!(a.cmplt(b).select(a, b), a.min(b));
+        assert_eq!(CanvasPoint::select(a.cmpgt(b), a, b), a.max(b));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{Aabb, CanvasPoint, Transform};
+
+/// An oriented bounding box: a box with half extents `half_extents` along its own axes,
+/// turned by `rotation` radians about `center` (the same sense as [`Transform::rotate`]).
+///
+/// Like [`Aabb`], boxes are closed, so touching counts as overlapping.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Obb {
+    pub center: CanvasPoint,
+    pub half_extents: CanvasPoint,
+    pub rotation: f32,
+}
+
+impl Obb {
+    pub fn new(center: CanvasPoint, half_extents: CanvasPoint, rotation: f32) -> Self {
+        Self {
+            center,
+            half_extents,
+            rotation,
+        }
+    }
+
+    /// The box `aabb` turned by `rotation` about its centre.
+    pub fn from_aabb(aabb: Aabb, rotation: f32) -> Self {
+        Self::new(
+            aabb.center(),
+            aabb.width_height() / CanvasPoint::splat(2.0),
+            rotation,
+        )
+    }
+
+    /// The box's own x and y axes as unit vectors.
+    pub fn axes(&self) -> [CanvasPoint; 2] {
+        let (sin, cos) = self.rotation.sin_cos();
+
+        [CanvasPoint::new(cos, sin), CanvasPoint::new(-sin, cos)]
+    }
+
+    /// The transform from the box's local frame, centred on the origin, to the canvas.
+    pub fn transform(&self) -> Transform {
+        Transform::rotate(self.rotation).then(Transform::translate(self.center))
+    }
+
+    /// The corners, starting from the one that is `min` on an unrotated box and going
+    /// round through the one that is `max`.
+    pub fn corners(&self) -> [CanvasPoint; 4] {
+        let (hx, hy) = self.half_extents.coord();
+        let t = self.transform();
+
+        [(-hx, -hy), (hx, -hy), (hx, hy), (-hx, hy)].map(|(x, y)| t.apply(CanvasPoint::new(x, y)))
+    }
+
+    /// The box that follows `transform`: its centre and x axis map exactly, and each half
+    /// extent scales with its axis. Boxes stay boxes, so shears are only approximated.
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        let [u, v] = self.axes().map(|axis| transform.apply_vector(axis));
+
+        Self::new(
+            transform.apply(self.center),
+            self.half_extents * CanvasPoint::new(u.length(), v.length()),
+            u.two().atan2(u.one()),
+        )
+    }
+
+    /// The smallest axis-aligned box around the rotated one.
+    pub fn bounds(&self) -> Aabb {
+        let [u, v] = self.axes();
+        let (hx, hy) = self.half_extents.coord();
+        let extent = (u * CanvasPoint::splat(hx)).abs() + (v * CanvasPoint::splat(hy)).abs();
+
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    /// `point` in the box's local frame, where the box spans `-half_extents..=half_extents`.
+    pub fn to_local(&self, point: CanvasPoint) -> CanvasPoint {
+        let [u, v] = self.axes();
+        let d = point - self.center;
+
+        CanvasPoint::new(d.dot(u), d.dot(v))
+    }
+
+    pub fn contains(&self, point: CanvasPoint) -> bool {
+        self.to_local(point).abs().cmple(self.half_extents).all()
+    }
+
+    /// Half the width of the box's shadow on `axis`, which must be a unit vector.
+    fn radius_along(&self, axis: CanvasPoint) -> f32 {
+        let [u, v] = self.axes();
+
+        (u.dot(axis) * self.half_extents.one()).abs()
+            + (v.dot(axis) * self.half_extents.two()).abs()
+    }
+
+    /// Separating axis test on the four edge normals of both boxes.
+    pub fn intersects(&self, other: Obb) -> bool {
+        let d = other.center - self.center;
+
+        self.axes()
+            .into_iter()
+            .chain(other.axes())
+            .all(|axis| d.dot(axis).abs() <= self.radius_along(axis) + other.radius_along(axis))
+    }
+
+    pub fn intersects_aabb(&self, aabb: Aabb) -> bool {
+        self.intersects(Obb::from_aabb(aabb, 0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    fn diamond() -> Obb {
+        // a 2 × 2 square stood on its corner, reaching √2 from the origin
+        Obb::new(CanvasPoint::splat(0.0), CanvasPoint::splat(1.0), FRAC_PI_4)
+    }
+
+    fn aabb(x1: f32, y1: f32, x2: f32, y2: f32) -> Aabb {
+        Aabb::new(CanvasPoint::new(x1, y1), CanvasPoint::new(x2, y2))
+    }
+
+    #[test]
+    fn contains() {
+        let d = diamond();
+
+        assert!(d.contains(CanvasPoint::new(1.4, 0.0)));
+        assert!(!d.contains(CanvasPoint::new(0.9, 0.9)));
+        assert!(d.contains(CanvasPoint::new(0.7, 0.7)));
+    }
+
+    #[test]
+    fn bounds_and_corners() {
+        let d = diamond();
+        let r = 2f32.sqrt();
+
+        assert_eq!(d.bounds(), aabb(-r, -r, r, r));
+        assert_eq!(Aabb::from_points(&d.corners()), Some(d.bounds()));
+
+        let square = aabb(1.0, 2.0, 5.0, 4.0);
+        let unrotated = Obb::from_aabb(square, 0.0);
+        assert_eq!(unrotated.bounds(), square);
+        assert_eq!(unrotated.corners()[0], square.min);
+        assert_eq!(unrotated.corners()[2], square.max);
+    }
+
+    #[test]
+    fn overlaps() {
+        let d = diamond();
+
+        // the corner box touches the diamond's bounds but not the diamond
+        assert!(d.bounds().intersects(aabb(0.8, 0.8, 2.0, 2.0)));
+        assert!(!d.intersects_aabb(aabb(0.8, 0.8, 2.0, 2.0)));
+        assert!(d.intersects_aabb(aabb(0.6, 0.6, 2.0, 2.0)));
+        assert!(d.intersects_aabb(aabb(-5.0, -5.0, 5.0, 5.0)));
+
+        // two diamonds side by side, just apart and then overlapping
+        let apart = Obb::new(
+            CanvasPoint::new(2.9, 0.0),
+            CanvasPoint::splat(1.0),
+            FRAC_PI_4,
+        );
+        let close = Obb::new(
+            CanvasPoint::new(2.7, 0.0),
+            CanvasPoint::splat(1.0),
+            FRAC_PI_4,
+        );
+        assert!(!d.intersects(apart));
+        assert!(d.intersects(close));
+
+        // a long thin bar whose bounds cover the diamond, but which passes beside it
+        let bar = Obb::new(
+            CanvasPoint::new(1.5, 1.5),
+            CanvasPoint::new(3.0, 0.1),
+            -FRAC_PI_4,
+        );
+        assert!(bar.bounds().intersects(d.bounds()));
+        assert!(!bar.intersects(d));
+    }
+
+    #[test]
+    fn transformed() {
+        let b = Obb::from_aabb(aabb(0.0, 0.0, 4.0, 2.0), 0.0);
+        let t = Transform::scale(CanvasPoint::new(2.0, 3.0))
+            .then(Transform::rotate(FRAC_PI_4))
+            .then(Transform::translate(CanvasPoint::new(1.0, 1.0)));
+        let image = b.transformed(&t);
+
+        assert!(image.center.approx_eq(t.apply(b.center), 1e-5));
+        assert!(image
+            .half_extents
+            .approx_eq(CanvasPoint::new(4.0, 3.0), 1e-5));
+        assert!((image.rotation - FRAC_PI_4).abs() < 1e-6);
+        for (corner, expected) in image.corners().iter().zip(b.corners()) {
+            assert!(corner.approx_eq(t.apply(expected), 1e-4));
+        }
+    }
+}
@@ -1,10 +1,11 @@
-use std::arch::wasm::{
-    f32x4, f32x4_abs, f32x4_add, f32x4_div, f32x4_extract_lane, f32x4_ge, f32x4_gt, f32x4_le,
-    f32x4_mul, f32x4_pmax, f32x4_pmin, f32x4_splat, f32x4_sqrt, f32x4_sub, i32x4_extract_lane,
-    v128,
+use crate::simd::{
+    f32x4, f32x4_abs, f32x4_add, f32x4_div, f32x4_eq, f32x4_extract_lane, f32x4_ge, f32x4_gt,
+    f32x4_le, f32x4_lt, f32x4_mul, f32x4_neg, f32x4_pmax, f32x4_pmin, f32x4_splat, f32x4_sqrt,
+    f32x4_sub, i32x4_shuffle, v128,
 };
+use crate::{predicates, BoolMask2};
 use std::cmp::Ordering;
-use std::ops::{Add, Div, Mul, Sub};
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
 #[derive(Debug, Clone, Copy)]
 pub struct CanvasPoint(pub v128);
@@ -14,12 +15,30 @@ impl CanvasPoint {
         Self(f32x4(x, y, 0.0, 0.0))
     }
 
+    pub fn splat(v: f32) -> Self {
+        Self::new(v, v)
+    }
+
     pub fn coord(self) -> (f32, f32) {
         (
             f32x4_extract_lane::<0>(self.0),
             f32x4_extract_lane::<1>(self.0),
         )
     }
+
+    /// Takes each lane from `if_true` where `mask` is set and from `if_false` elsewhere.
+    pub fn select(mask: BoolMask2, if_true: Self, if_false: Self) -> Self {
+        mask.select(if_true, if_false)
+    }
+
+    /// A total order, by x and then by y, with each lane ordered as [`f32::total_cmp`].
+    /// Points have no natural order, so this exists for sorting and deduplication.
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        let (ax, ay) = self.coord();
+        let (bx, by) = other.coord();
+
+        ax.total_cmp(&bx).then(ay.total_cmp(&by))
+    }
 }
 
 macro_rules! impl_math {
@@ -57,6 +76,14 @@ macro_rules! impl_math {
             }
         }
 
+        impl Neg for $ty {
+            type Output = $ty;
+
+            fn neg(self) -> Self::Output {
+                <$ty>::from_v128(f32x4_neg(self.0))
+            }
+        }
+
         impl $ty {
             #[inline(always)]
             pub fn from_v128(vector: v128) -> Self {
@@ -68,11 +95,20 @@ macro_rules! impl_math {
                 <$ty>::from_v128(f32x4_abs(self.0))
             }
 
+            /// Whether both lanes differ by at most `epsilon`.
             #[inline(always)]
-            fn approx_eq(self, other: Self, epsilon: f32) -> bool {
+            pub fn approx_eq(self, other: Self, epsilon: f32) -> bool {
                 let diff = (self - other).abs();
 
-                diff < <$ty>::from_v128(f32x4_splat(epsilon))
+                diff.cmple(<$ty>::from_v128(f32x4_splat(epsilon))).all()
+            }
+
+            /// Lane-wise [`predicates::relative_eq`], for comparisons that must hold far from
+            /// the origin as well as near it.
+            #[inline(always)]
+            pub fn relative_eq(self, other: Self, tolerance: f32) -> bool {
+                predicates::relative_eq(self.one(), other.one(), tolerance)
+                    && predicates::relative_eq(self.two(), other.two(), tolerance)
             }
 
             #[inline(always)]
@@ -91,10 +127,28 @@ macro_rules! impl_math {
             }
 
             #[inline(always)]
-            pub fn le_or(self, other: $ty) -> bool {
-                let res = f32x4_le(self.0, other.0);
+            pub fn cmpeq(self, other: $ty) -> BoolMask2 {
+                BoolMask2::from_v128(f32x4_eq(self.0, other.0))
+            }
+
+            #[inline(always)]
+            pub fn cmplt(self, other: $ty) -> BoolMask2 {
+                BoolMask2::from_v128(f32x4_lt(self.0, other.0))
+            }
+
+            #[inline(always)]
+            pub fn cmple(self, other: $ty) -> BoolMask2 {
+                BoolMask2::from_v128(f32x4_le(self.0, other.0))
+            }
+
+            #[inline(always)]
+            pub fn cmpgt(self, other: $ty) -> BoolMask2 {
+                BoolMask2::from_v128(f32x4_gt(self.0, other.0))
+            }
 
-                i32x4_extract_lane::<0>(res) | i32x4_extract_lane::<1>(res) == -1
+            #[inline(always)]
+            pub fn cmpge(self, other: $ty) -> BoolMask2 {
+                BoolMask2::from_v128(f32x4_ge(self.0, other.0))
             }
 
             #[inline(always)]
@@ -112,6 +166,33 @@ macro_rules! impl_math {
                 f32x4_extract_lane::<0>(self.0) + f32x4_extract_lane::<1>(self.0)
             }
 
+            #[inline(always)]
+            pub fn dot(self, other: $ty) -> f32 {
+                (self * other).sum()
+            }
+
+            /// The z component of the 3D cross product, positive when `other` is counter-clockwise
+            /// from `self` in a y-up frame.
+            #[inline(always)]
+            pub fn cross(self, other: $ty) -> f32 {
+                let swapped = <$ty>::from_v128(i32x4_shuffle::<1, 0, 2, 3>(other.0, other.0));
+                let products = self * swapped;
+
+                products.one() - products.two()
+            }
+
+            #[inline(always)]
+            pub fn length(self) -> f32 {
+                self.dot(self).sqrt()
+            }
+
+            /// Scales to unit length. The zero vector has no direction and yields NaN lanes.
+            #[inline(always)]
+            pub fn normalize(self) -> $ty {
+                let length = self.length();
+                self / <$ty>::new(length, length)
+            }
+
             #[inline(always)]
             pub fn euclid_dist(self, other: $ty) -> f32 {
                 let diff = self - other;
@@ -129,47 +210,20 @@ macro_rules! impl_math {
             pub fn midpoint(self, other: $ty) -> $ty {
                 (self + other) / <$ty>::new(2.0, 2.0)
             }
+
+            #[inline(always)]
+            pub fn lerp(self, other: $ty, t: f32) -> $ty {
+                self + (other - self) * <$ty>::new(t, t)
+            }
         }
 
+        /// Equal within an absolute `1e-6` per lane, which is exact equality once
+        /// coordinates pass 16. Use `relative_eq` where that matters.
         impl PartialEq for $ty {
             fn eq(&self, other: &Self) -> bool {
                 self.approx_eq(*other, 1e-6)
             }
         }
-
-        impl PartialOrd for $ty {
-            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-                if self.lt(other) {
-                    Some(Ordering::Less)
-                } else if self.gt(other) {
-                    Some(Ordering::Greater)
-                } else if self.eq(other) {
-                    Some(Ordering::Equal)
-                } else {
-                    None
-                }
-            }
-
-            fn lt(&self, other: &Self) -> bool {
-                let res = f32x4_le(self.0, other.0);
-                i32x4_extract_lane::<0>(res) & i32x4_extract_lane::<1>(res) == -1
-            }
-
-            fn le(&self, other: &Self) -> bool {
-                let res = f32x4_le(self.0, other.0);
-                i32x4_extract_lane::<0>(res) & i32x4_extract_lane::<1>(res) == -1
-            }
-
-            fn gt(&self, other: &Self) -> bool {
-                let res = f32x4_gt(self.0, other.0);
-                i32x4_extract_lane::<0>(res) & i32x4_extract_lane::<1>(res) == -1
-            }
-
-            fn ge(&self, other: &Self) -> bool {
-                let res = f32x4_ge(self.0, other.0);
-                i32x4_extract_lane::<0>(res) & i32x4_extract_lane::<1>(res) == -1
-            }
-        }
     };
 }
 
@@ -177,11 +231,12 @@ impl_math!(CanvasPoint);
 
 #[cfg(test)]
 mod tests {
-    use wasm_bindgen_test::wasm_bindgen_test;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
 
     use super::*;
 
-    #[wasm_bindgen_test]
+    #[test]
     fn eq() {
         let p1 = CanvasPoint::new(2.4, 3.5);
         let p2 = CanvasPoint::new(2.4, 3.5);
@@ -191,7 +246,7 @@ mod tests {
         assert_ne!(p1, p3);
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn add_basic() {
         let p1 = CanvasPoint::new(0.0, 2.0);
         let p2 = CanvasPoint::new(3.2, 4.3);
@@ -199,7 +254,7 @@ mod tests {
         assert_eq!(p1 + p2, CanvasPoint::new(3.2, 6.3));
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn clamp() {
         assert_eq!(
             CanvasPoint::new(34.5, 70.40),
@@ -207,4 +262,51 @@ mod tests {
                 .clamp(CanvasPoint::new(20.30, 70.40), CanvasPoint::new(45.0, 78.9))
         )
     }
+
+    #[test]
+    fn relative_eq() {
+        let far = CanvasPoint::new(3.0e7, -3.0e7);
+
+        assert_ne!(far, far + CanvasPoint::new(2.0, 0.0));
+        assert!(far.relative_eq(far + CanvasPoint::new(2.0, -2.0), 1e-6));
+        assert!(!far.relative_eq(far + CanvasPoint::new(64.0, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn lane_masks() {
+        let (a, b) = (CanvasPoint::new(1.0, 5.0), CanvasPoint::new(1.0, 2.0));
+
+        assert_eq!(a.cmpeq(b), BoolMask2::new(true, false));
+        assert_eq!(a.cmplt(b), BoolMask2::new(false, false));
+        assert_eq!(a.cmple(b), BoolMask2::new(true, false));
+        assert_eq!(a.cmpgt(b), BoolMask2::new(false, true));
+        assert_eq!(a.cmpge(b), BoolMask2::new(true, true));
+
+        // NaN compares false in every lane-wise test
+        let nan = CanvasPoint::new(f32::NAN, 0.0);
+        assert!(!nan.cmpeq(nan).x() && nan.cmpge(nan).y());
+    }
+
+    #[test]
+    fn total_cmp() {
+        let mut points = [
+            CanvasPoint::new(2.0, 1.0),
+            CanvasPoint::new(1.0, 3.0),
+            CanvasPoint::new(2.0, -1.0),
+            CanvasPoint::new(1.0, 0.0),
+        ];
+        points.sort_by(CanvasPoint::total_cmp);
+
+        assert_eq!(
+            points,
+            [
+                CanvasPoint::new(1.0, 0.0),
+                CanvasPoint::new(1.0, 3.0),
+                CanvasPoint::new(2.0, -1.0),
+                CanvasPoint::new(2.0, 1.0),
+            ]
+        );
+        // neither is below the other on both axes, yet the order is still decided
+        assert_eq!(points[1].total_cmp(&points[2]), Ordering::Less);
+    }
 }
@@ -0,0 +1,318 @@
+use crate::simd::{
+    f32x4, f32x4_add, f32x4_div, f32x4_extract_lane, f32x4_mul, f32x4_pmax, f32x4_pmin,
+    f32x4_splat, f32x4_sub, i32x4_shuffle, v128,
+};
+use crate::{Aabb, CanvasPoint, Segment, Transform};
+
+/// A structure-of-arrays point list. The x and y coordinates live in separate
+/// `v128` chunks of four, so every operation handles four points per instruction.
+///
+/// Unused lanes of the last chunk repeat the last point. They never change a bound
+/// or a minimum, and segments into them have zero length.
+#[derive(Debug, Clone, Default)]
+pub struct PointBuffer {
+    xs: Vec<v128>,
+    ys: Vec<v128>,
+    len: usize,
+}
+
+#[inline(always)]
+fn lane(v: v128, i: usize) -> f32 {
+    match i {
+        0 => f32x4_extract_lane::<0>(v),
+        1 => f32x4_extract_lane::<1>(v),
+        2 => f32x4_extract_lane::<2>(v),
+        _ => f32x4_extract_lane::<3>(v),
+    }
+}
+
+#[inline(always)]
+fn horizontal_min(v: v128) -> f32 {
+    (0..4).map(|i| lane(v, i)).fold(f32::INFINITY, f32::min)
+}
+
+#[inline(always)]
+fn horizontal_max(v: v128) -> f32 {
+    (0..4).map(|i| lane(v, i)).fold(f32::NEG_INFINITY, f32::max)
+}
+
+impl PointBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, point: CanvasPoint) {
+        let (x, y) = point.coord();
+        let offset = self.len % 4;
+
+        if offset == 0 {
+            self.xs.push(f32x4_splat(x));
+            self.ys.push(f32x4_splat(y));
+        } else {
+            // overwrite this lane and the padding after it
+            let fill = |chunk: v128, v: f32| {
+                let lanes: [f32; 4] =
+                    std::array::from_fn(|i| if i < offset { lane(chunk, i) } else { v });
+                f32x4(lanes[0], lanes[1], lanes[2], lanes[3])
+            };
+
+            let last = self.xs.len() - 1;
+            self.xs[last] = fill(self.xs[last], x);
+            self.ys[last] = fill(self.ys[last], y);
+        }
+
+        self.len += 1;
+    }
+
+    pub fn get(&self, index: usize) -> Option<CanvasPoint> {
+        (index < self.len).then(|| {
+            CanvasPoint::new(
+                lane(self.xs[index / 4], index % 4),
+                lane(self.ys[index / 4], index % 4),
+            )
+        })
+    }
+
+    pub fn first(&self) -> Option<CanvasPoint> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<CanvasPoint> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = CanvasPoint> + '_ {
+        (0..self.len).filter_map(|i| self.get(i))
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        self.iter()
+            .zip(self.iter().skip(1))
+            .map(|(a, b)| Segment::new(a, b))
+    }
+
+    pub fn to_vec(&self) -> Vec<CanvasPoint> {
+        self.iter().collect()
+    }
+
+    pub fn translate(&mut self, offset: CanvasPoint) {
+        let (dx, dy) = offset.coord();
+        let (dx, dy) = (f32x4_splat(dx), f32x4_splat(dy));
+
+        self.xs.iter_mut().for_each(|x| *x = f32x4_add(*x, dx));
+        self.ys.iter_mut().for_each(|y| *y = f32x4_add(*y, dy));
+    }
+
+    pub fn transform(&mut self, transform: &Transform) {
+        let (a, b) = transform.x_axis.coord();
+        let (c, d) = transform.y_axis.coord();
+        let (e, f) = transform.translation.coord();
+        let (a, b, c, d) = (
+            f32x4_splat(a),
+            f32x4_splat(b),
+            f32x4_splat(c),
+            f32x4_splat(d),
+        );
+        let (e, f) = (f32x4_splat(e), f32x4_splat(f));
+
+        for (x, y) in self.xs.iter_mut().zip(self.ys.iter_mut()) {
+            let new_x = f32x4_add(f32x4_add(f32x4_mul(a, *x), f32x4_mul(c, *y)), e);
+            let new_y = f32x4_add(f32x4_add(f32x4_mul(b, *x), f32x4_mul(d, *y)), f);
+
+            *x = new_x;
+            *y = new_y;
+        }
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        let (&x0, &y0) = (self.xs.first()?, self.ys.first()?);
+        let init = (x0, y0, x0, y0);
+
+        let (min_x, min_y, max_x, max_y) = self.xs.iter().zip(self.ys.iter()).fold(
+            init,
+            |(min_x, min_y, max_x, max_y), (&x, &y)| {
+                (
+                    f32x4_pmin(min_x, x),
+                    f32x4_pmin(min_y, y),
+                    f32x4_pmax(max_x, x),
+                    f32x4_pmax(max_y, y),
+                )
+            },
+        );
+
+        Some(Aabb::new(
+            CanvasPoint::new(horizontal_min(min_x), horizontal_min(min_y)),
+            CanvasPoint::new(horizontal_max(max_x), horizontal_max(max_y)),
+        ))
+    }
+
+    /// Distance from `point` to the nearest point in the buffer, or infinity when empty.
+    pub fn nearest_distance(&self, point: CanvasPoint) -> f32 {
+        let (px, py) = point.coord();
+        let (px, py) = (f32x4_splat(px), f32x4_splat(py));
+
+        self.xs
+            .iter()
+            .zip(self.ys.iter())
+            .map(|(&x, &y)| {
+                let dx = f32x4_sub(x, px);
+                let dy = f32x4_sub(y, py);
+
+                horizontal_min(f32x4_add(f32x4_mul(dx, dx), f32x4_mul(dy, dy)))
+            })
+            .fold(f32::INFINITY, f32::min)
+            .sqrt()
+    }
+
+    /// Distance from `point` to the polyline through the buffer, testing four segments
+    /// per iteration. A single point is its own polyline.
+    pub fn polyline_distance(&self, point: CanvasPoint) -> f32 {
+        let (px, py) = point.coord();
+        let (px, py) = (f32x4_splat(px), f32x4_splat(py));
+        let (zero, one) = (f32x4_splat(0.0), f32x4_splat(1.0));
+        let tiny = f32x4_splat(f32::MIN_POSITIVE);
+
+        (0..self.xs.len())
+            .map(|k| {
+                // segment i runs from lane i of this chunk to lane i + 1, which spills into the next chunk
+                let (ax, ay) = (self.xs[k], self.ys[k]);
+                let (nx, ny) = match (self.xs.get(k + 1), self.ys.get(k + 1)) {
+                    (Some(&nx), Some(&ny)) => (nx, ny),
+                    // the final lane holds the last point, so broadcasting it closes nothing
+                    _ => (
+                        i32x4_shuffle::<3, 3, 3, 3>(ax, ax),
+                        i32x4_shuffle::<3, 3, 3, 3>(ay, ay),
+                    ),
+                };
+                let bx = i32x4_shuffle::<1, 2, 3, 4>(ax, nx);
+                let by = i32x4_shuffle::<1, 2, 3, 4>(ay, ny);
+
+                let (dx, dy) = (f32x4_sub(bx, ax), f32x4_sub(by, ay));
+                let (ex, ey) = (f32x4_sub(px, ax), f32x4_sub(py, ay));
+
+                let len_sq = f32x4_pmax(f32x4_add(f32x4_mul(dx, dx), f32x4_mul(dy, dy)), tiny);
+                let dot = f32x4_add(f32x4_mul(ex, dx), f32x4_mul(ey, dy));
+                let t = f32x4_pmin(f32x4_pmax(f32x4_div(dot, len_sq), zero), one);
+
+                let cx = f32x4_sub(ex, f32x4_mul(t, dx));
+                let cy = f32x4_sub(ey, f32x4_mul(t, dy));
+
+                horizontal_min(f32x4_add(f32x4_mul(cx, cx), f32x4_mul(cy, cy)))
+            })
+            .fold(f32::INFINITY, f32::min)
+            .sqrt()
+    }
+}
+
+impl PartialEq for PointBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl From<&[CanvasPoint]> for PointBuffer {
+    fn from(points: &[CanvasPoint]) -> Self {
+        points.iter().copied().collect()
+    }
+}
+
+impl From<Vec<CanvasPoint>> for PointBuffer {
+    fn from(points: Vec<CanvasPoint>) -> Self {
+        points.into_iter().collect()
+    }
+}
+
+impl FromIterator<CanvasPoint> for PointBuffer {
+    fn from_iter<I: IntoIterator<Item = CanvasPoint>>(iter: I) -> Self {
+        let mut buffer = Self::new();
+        iter.into_iter().for_each(|p| buffer.push(p));
+
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::polyline;
+
+    fn points() -> Vec<CanvasPoint> {
+        (0..7)
+            .map(|i| CanvasPoint::new(i as f32 * 10.0, if i % 2 == 0 { 0.0 } else { 10.0 }))
+            .collect()
+    }
+
+    #[test]
+    fn push_and_get() {
+        let buffer = PointBuffer::from(points());
+
+        assert_eq!(buffer.len(), 7);
+        assert_eq!(buffer.to_vec(), points());
+        assert_eq!(buffer.last(), Some(CanvasPoint::new(60.0, 0.0)));
+        assert_eq!(buffer.get(7), None);
+        assert_eq!(buffer.segments().count(), 6);
+    }
+
+    #[test]
+    fn transform_matches_scalar() {
+        let t = Transform::rotate(0.7).then(Transform::translate(CanvasPoint::new(3.0, -2.0)));
+        let mut buffer = PointBuffer::from(points());
+        buffer.transform(&t);
+
+        assert_eq!(buffer.to_vec(), t.apply_all(&points()));
+
+        buffer.translate(CanvasPoint::new(1.0, 1.0));
+        assert_eq!(
+            buffer.get(2),
+            Some(t.apply(points()[2]) + CanvasPoint::new(1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn bounds() {
+        let buffer = PointBuffer::from(points());
+
+        assert_eq!(buffer.bounds(), Aabb::from_points(&points()));
+        assert_eq!(PointBuffer::new().bounds(), None);
+    }
+
+    #[test]
+    fn distances_match_scalar() {
+        let buffer = PointBuffer::from(points());
+
+        for p in [
+            CanvasPoint::new(15.0, 2.0),
+            CanvasPoint::new(65.0, 3.0),
+            CanvasPoint::new(-4.0, 9.0),
+            CanvasPoint::new(31.0, 20.0),
+        ] {
+            let expected = polyline::distance(&points(), p);
+            assert!((buffer.polyline_distance(p) - expected).abs() < 1e-4);
+
+            let nearest = points()
+                .iter()
+                .map(|q| q.euclid_dist(p))
+                .fold(f32::INFINITY, f32::min);
+            assert!((buffer.nearest_distance(p) - nearest).abs() < 1e-4);
+        }
+
+        // a full final chunk must not wrap its last point back to its first
+        let full = PointBuffer::from(&points()[..4]);
+        let expected = polyline::distance(&points()[..4], CanvasPoint::new(9.0, 3.0));
+        assert!((full.polyline_distance(CanvasPoint::new(9.0, 3.0)) - expected).abs() < 1e-4);
+
+        let single = PointBuffer::from(vec![CanvasPoint::new(0.0, 0.0)]);
+        assert_eq!(single.polyline_distance(CanvasPoint::new(3.0, 4.0)), 5.0);
+    }
+}
@@ -0,0 +1,222 @@
+//! Operations on closed polygons given as point slices, where the last vertex
+//! connects back to the first.
+//!
+//! Orientation follows the usual y-up convention: counter-clockwise polygons have a
+//! positive signed area. On screen, where y grows downwards, they appear clockwise.
+
+use crate::predicates::orient2d;
+use crate::{CanvasPoint, Segment};
+
+pub fn edges(points: &[CanvasPoint]) -> impl Iterator<Item = Segment> + '_ {
+    let next = points.iter().cycle().skip(1);
+
+    points
+        .iter()
+        .zip(next)
+        .map(|(&from, &to)| Segment::new(from, to))
+}
+
+/// Shoelace area, positive for counter-clockwise vertex order.
+pub fn signed_area(points: &[CanvasPoint]) -> f32 {
+    edges(points).map(|e| e.from.cross(e.to)).sum::<f32>() / 2.0
+}
+
+pub fn area(points: &[CanvasPoint]) -> f32 {
+    signed_area(points).abs()
+}
+
+/// The area centroid, or `None` for polygons with no area.
+pub fn centroid(points: &[CanvasPoint]) -> Option<CanvasPoint> {
+    let signed_area = signed_area(points);
+
+    if signed_area == 0.0 {
+        return None;
+    }
+
+    let sum = edges(points).fold(CanvasPoint::splat(0.0), |acc, e| {
+        acc + (e.from + e.to) * CanvasPoint::splat(e.from.cross(e.to))
+    });
+
+    Some(sum / CanvasPoint::splat(6.0 * signed_area))
+}
+
+/// How many times the polygon winds counter-clockwise around `point`. Clockwise turns
+/// count negatively.
+pub fn winding_number(points: &[CanvasPoint], point: CanvasPoint) -> i32 {
+    edges(points)
+        .map(|e| {
+            let side = orient2d(e.from, e.to, point);
+
+            if e.from.two() <= point.two() {
+                // upward crossing with the point on the left
+                (e.to.two() > point.two() && side > 0.0) as i32
+            } else {
+                // downward crossing with the point on the right
+                -((e.to.two() <= point.two() && side < 0.0) as i32)
+            }
+        })
+        .sum()
+}
+
+/// Point containment under the nonzero fill rule, the SVG default.
+pub fn contains(points: &[CanvasPoint], point: CanvasPoint) -> bool {
+    winding_number(points, point) != 0
+}
+
+/// Convex hull with Andrew's monotone chain, in counter-clockwise order without
+/// collinear vertices.
+pub fn convex_hull(points: &[CanvasPoint]) -> Vec<CanvasPoint> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(CanvasPoint::total_cmp);
+    sorted.dedup();
+
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut lower = half_hull(sorted.iter());
+    let mut upper = half_hull(sorted.iter().rev());
+
+    // each half ends where the other begins
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+
+    lower
+}
+
+/// One side of the monotone chain, keeping only strict left turns.
+fn half_hull<'a>(points: impl Iterator<Item = &'a CanvasPoint>) -> Vec<CanvasPoint> {
+    let mut hull: Vec<CanvasPoint> = Vec::new();
+
+    for &p in points {
+        while let [.., a, b] = hull[..] {
+            if orient2d(a, b, p) > 0.0 {
+                break;
+            }
+            hull.pop();
+        }
+        hull.push(p);
+    }
+
+    hull
+}
+
+/// Separating axis test for two convex polygons. Touching polygons overlap.
+pub fn convex_overlaps(a: &[CanvasPoint], b: &[CanvasPoint]) -> bool {
+    let project = |points: &[CanvasPoint], axis: CanvasPoint| {
+        points
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), p| {
+                let d = p.dot(axis);
+                (lo.min(d), hi.max(d))
+            })
+    };
+
+    edges(a).chain(edges(b)).all(|e| {
+        let (dx, dy) = e.direction().coord();
+        let normal = CanvasPoint::new(-dy, dx);
+        let (a_lo, a_hi) = project(a, normal);
+        let (b_lo, b_hi) = project(b, normal);
+
+        a_lo <= b_hi && b_lo <= a_hi
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn polygon(coords: &[(f32, f32)]) -> Vec<CanvasPoint> {
+        coords
+            .iter()
+            .map(|&(x, y)| CanvasPoint::new(x, y))
+            .collect()
+    }
+
+    fn square() -> Vec<CanvasPoint> {
+        polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)])
+    }
+
+    #[test]
+    fn area_and_orientation() {
+        let mut points = square();
+
+        assert_eq!(signed_area(&points), 16.0);
+        points.reverse();
+        assert_eq!(signed_area(&points), -16.0);
+        assert_eq!(area(&points), 16.0);
+    }
+
+    #[test]
+    fn centroid_of_l_shape() {
+        let points = polygon(&[
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 2.0),
+            (2.0, 2.0),
+            (2.0, 4.0),
+            (0.0, 4.0),
+        ]);
+        let c = centroid(&points).unwrap();
+
+        assert!(c.euclid_dist(CanvasPoint::splat(5.0 / 3.0)) < 1e-5);
+        assert_eq!(centroid(&polygon(&[(0.0, 0.0), (1.0, 1.0)])), None);
+    }
+
+    #[test]
+    fn point_in_polygon() {
+        let points = square();
+
+        assert!(contains(&points, CanvasPoint::new(2.0, 2.0)));
+        assert!(!contains(&points, CanvasPoint::new(5.0, 2.0)));
+        assert_eq!(winding_number(&points, CanvasPoint::new(2.0, 2.0)), 1);
+
+        // a pentagram winds twice around its core
+        let star = polygon(&[
+            (0.0, 10.0),
+            (5.9, -8.1),
+            (-9.5, 3.1),
+            (9.5, 3.1),
+            (-5.9, -8.1),
+        ]);
+        assert_eq!(winding_number(&star, CanvasPoint::new(0.0, 0.0)).abs(), 2);
+        assert!(contains(&star, CanvasPoint::new(0.0, 8.0)));
+        assert!(!contains(&star, CanvasPoint::new(8.0, 8.0)));
+    }
+
+    #[test]
+    fn hull() {
+        let points = polygon(&[
+            (0.0, 0.0),
+            (2.0, 1.0),
+            (4.0, 0.0),
+            (2.0, 0.0),
+            (4.0, 4.0),
+            (1.0, 2.0),
+            (0.0, 4.0),
+            (0.0, 4.0),
+        ]);
+
+        assert_eq!(convex_hull(&points), square());
+        assert_eq!(convex_hull(&points[..2]), points[..2].to_vec());
+    }
+
+    #[test]
+    fn sat_overlap() {
+        let a = square();
+        let diamond = |cx: f32| polygon(&[(cx, -2.0), (cx + 2.0, 0.0), (cx, 2.0), (cx - 2.0, 0.0)]);
+
+        assert!(convex_overlaps(&a, &diamond(5.0)));
+        assert!(convex_overlaps(&a, &diamond(6.0)));
+        assert!(!convex_overlaps(&a, &diamond(6.5)));
+        // the bounding boxes overlap but the shapes do not
+        assert!(!convex_overlaps(
+            &diamond(0.0),
+            &polygon(&[(1.5, 1.5), (3.0, 1.5), (3.0, 3.0)])
+        ));
+    }
+}
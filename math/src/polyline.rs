@@ -0,0 +1,91 @@
+//! Operations on open polylines given as point slices, such as freehand strokes.
+
+use crate::{Aabb, CanvasPoint, Segment};
+
+pub fn segments(points: &[CanvasPoint]) -> impl Iterator<Item = Segment> + '_ {
+    points.windows(2).map(|p| Segment::new(p[0], p[1]))
+}
+
+pub fn length(points: &[CanvasPoint]) -> f32 {
+    segments(points).map(|s| s.length()).sum()
+}
+
+/// Distance from `point` to the nearest part of the polyline, or infinity when it is empty.
+pub fn distance(points: &[CanvasPoint], point: CanvasPoint) -> f32 {
+    match points {
+        [] => f32::INFINITY,
+        [p] => p.euclid_dist(point),
+        _ => segments(points)
+            .map(|s| s.distance(point))
+            .fold(f32::INFINITY, f32::min),
+    }
+}
+
+/// Whether `point` lies within `tolerance` of the polyline.
+pub fn hit_test(points: &[CanvasPoint], point: CanvasPoint, tolerance: f32) -> bool {
+    match Aabb::from_points(points) {
+        Some(bounds) if bounds.expand(tolerance).contains(point) => {
+            distance(points, point) <= tolerance
+        }
+        _ => false,
+    }
+}
+
+/// Whether any part of the polyline touches `aabb`.
+pub fn intersects_aabb(points: &[CanvasPoint], aabb: Aabb) -> bool {
+    match Aabb::from_points(points) {
+        Some(bounds) if bounds.intersects(aabb) => {
+            points.iter().any(|&p| aabb.contains(p))
+                || segments(points).any(|s| s.intersects_aabb(aabb))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn zigzag() -> Vec<CanvasPoint> {
+        vec![
+            CanvasPoint::new(0.0, 0.0),
+            CanvasPoint::new(10.0, 10.0),
+            CanvasPoint::new(20.0, 0.0),
+        ]
+    }
+
+    #[test]
+    fn length_and_distance() {
+        let points = zigzag();
+
+        assert!((length(&points) - 2.0 * 200f32.sqrt()).abs() < 1e-4);
+        assert_eq!(distance(&points, CanvasPoint::new(10.0, 14.0)), 4.0);
+        assert_eq!(distance(&[], CanvasPoint::new(0.0, 0.0)), f32::INFINITY);
+    }
+
+    #[test]
+    fn hit_test_tolerance() {
+        let points = zigzag();
+
+        assert!(hit_test(&points, CanvasPoint::new(10.0, 12.0), 2.0));
+        assert!(!hit_test(&points, CanvasPoint::new(10.0, 12.5), 2.0));
+        assert!(!hit_test(&points, CanvasPoint::new(10.0, 0.0), 2.0));
+    }
+
+    #[test]
+    fn intersects_box() {
+        let points = zigzag();
+        let b = |x1, y1, x2, y2| Aabb::new(CanvasPoint::new(x1, y1), CanvasPoint::new(x2, y2));
+
+        // contains a vertex
+        assert!(intersects_aabb(&points, b(8.0, 8.0, 12.0, 12.0)));
+        // crossed by a segment without containing a vertex
+        assert!(intersects_aabb(&points, b(4.0, 3.0, 6.0, 7.0)));
+        // inside the zigzag's bounds but between its segments
+        assert!(!intersects_aabb(&points, b(8.0, 0.0, 12.0, 4.0)));
+        assert!(!intersects_aabb(&[], b(0.0, 0.0, 1.0, 1.0)));
+    }
+}
@@ -0,0 +1,290 @@
+//! Robust geometric predicates and tolerance-aware comparisons.
+//!
+//! The orientation and incircle tests follow Shewchuk's adaptive approach: the
+//! determinant is first evaluated in `f64` with a bound on its rounding error, and only
+//! when the sign is in doubt is it recomputed exactly with floating point expansions.
+//! Their signs are always correct, however far from the origin the points are.
+//!
+//! Signs follow the y-up convention of [`crate::polygon`]: counter-clockwise is positive,
+//! which is clockwise on screen.
+
+use crate::CanvasPoint;
+
+const EPSILON: f64 = f64::EPSILON / 2.0;
+const ORIENT_ERROR_BOUND: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const INCIRCLE_ERROR_BOUND: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+
+fn coords(p: CanvasPoint) -> (f64, f64) {
+    let (x, y) = p.coord();
+    (x as f64, y as f64)
+}
+
+/// Positive when `a`, `b`, `c` turn counter-clockwise, negative when clockwise and zero
+/// when they are collinear. The magnitude is twice the triangle's area, approximately;
+/// only the sign is exact.
+pub fn orient2d(a: CanvasPoint, b: CanvasPoint, c: CanvasPoint) -> f64 {
+    let ((ax, ay), (bx, by), (cx, cy)) = (coords(a), coords(b), coords(c));
+
+    let left = (ax - cx) * (by - cy);
+    let right = (ay - cy) * (bx - cx);
+    let det = left - right;
+
+    if det.abs() > ORIENT_ERROR_BOUND * (left.abs() + right.abs()) {
+        return det;
+    }
+
+    orient2d_exact(a, b, c)
+}
+
+fn orient2d_exact(a: CanvasPoint, b: CanvasPoint, c: CanvasPoint) -> f64 {
+    let ((ax, ay), (bx, by), (cx, cy)) = (coords(a), coords(b), coords(c));
+
+    let left = product(&two_diff(ax, cx), &two_diff(by, cy));
+    let right = product(&two_diff(ay, cy), &two_diff(bx, cx));
+
+    estimate(&sum(&left, &negate(&right)))
+}
+
+/// Positive when `d` lies inside the circle through `a`, `b`, `c`, negative outside and
+/// zero on it. `a`, `b`, `c` must turn counter-clockwise, otherwise the sign flips.
+pub fn incircle(a: CanvasPoint, b: CanvasPoint, c: CanvasPoint, d: CanvasPoint) -> f64 {
+    let ((ax, ay), (bx, by), (cx, cy), (dx, dy)) = (coords(a), coords(b), coords(c), coords(d));
+
+    let (adx, ady) = (ax - dx, ay - dy);
+    let (bdx, bdy) = (bx - dx, by - dy);
+    let (cdx, cdy) = (cx - dx, cy - dy);
+
+    let (bc, cb) = (bdx * cdy, cdx * bdy);
+    let (ca, ac) = (cdx * ady, adx * cdy);
+    let (ab, ba) = (adx * bdy, bdx * ady);
+
+    let alift = adx * adx + ady * ady;
+    let blift = bdx * bdx + bdy * bdy;
+    let clift = cdx * cdx + cdy * cdy;
+
+    let det = alift * (bc - cb) + blift * (ca - ac) + clift * (ab - ba);
+    let permanent = (bc.abs() + cb.abs()) * alift
+        + (ca.abs() + ac.abs()) * blift
+        + (ab.abs() + ba.abs()) * clift;
+
+    if det.abs() > INCIRCLE_ERROR_BOUND * permanent {
+        return det;
+    }
+
+    incircle_exact(a, b, c, d)
+}
+
+fn incircle_exact(a: CanvasPoint, b: CanvasPoint, c: CanvasPoint, d: CanvasPoint) -> f64 {
+    let ((ax, ay), (bx, by), (cx, cy), (dx, dy)) = (coords(a), coords(b), coords(c), coords(d));
+
+    let (adx, ady) = (two_diff(ax, dx), two_diff(ay, dy));
+    let (bdx, bdy) = (two_diff(bx, dx), two_diff(by, dy));
+    let (cdx, cdy) = (two_diff(cx, dx), two_diff(cy, dy));
+
+    let lift = |x: &[f64], y: &[f64]| sum(&product(x, x), &product(y, y));
+    let cross = |x1: &[f64], y2: &[f64], x2: &[f64], y1: &[f64]| {
+        sum(&product(x1, y2), &negate(&product(x2, y1)))
+    };
+
+    let a_term = product(&lift(&adx, &ady), &cross(&bdx, &cdy, &cdx, &bdy));
+    let b_term = product(&lift(&bdx, &bdy), &cross(&cdx, &ady, &adx, &cdy));
+    let c_term = product(&lift(&cdx, &cdy), &cross(&adx, &bdy, &bdx, &ady));
+
+    estimate(&sum(&sum(&a_term, &b_term), &c_term))
+}
+
+// Floating point expansions: sums of non-overlapping f64 components in increasing
+// magnitude, which represent a real number exactly.
+
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+
+fn two_diff(a: f64, b: f64) -> [f64; 2] {
+    let (x, y) = two_sum(a, -b);
+    [y, x]
+}
+
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+/// Adds `b` to the expansion `e`.
+fn grow(e: &[f64], b: f64) -> Vec<f64> {
+    let mut q = b;
+    let mut h = Vec::with_capacity(e.len() + 1);
+
+    for &component in e {
+        let (sum, error) = two_sum(q, component);
+        if error != 0.0 {
+            h.push(error);
+        }
+        q = sum;
+    }
+
+    h.push(q);
+    h
+}
+
+fn sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter()
+        .fold(e.to_vec(), |acc, &component| grow(&acc, component))
+}
+
+fn negate(e: &[f64]) -> Vec<f64> {
+    e.iter().map(|c| -c).collect()
+}
+
+fn scale(e: &[f64], b: f64) -> Vec<f64> {
+    e.iter().fold(Vec::new(), |acc, &component| {
+        let (x, y) = two_product(component, b);
+        sum(&acc, &[y, x])
+    })
+}
+
+fn product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(Vec::new(), |acc, &component| {
+        sum(&acc, &scale(e, component))
+    })
+}
+
+/// The expansion's value to within rounding. Its components do not overlap, so the sum
+/// always has the sign of the largest one, which is the sign of the whole.
+fn estimate(e: &[f64]) -> f64 {
+    e.iter().sum()
+}
+
+/// Whether `a` and `b` differ by at most `tolerance`.
+pub fn approx_eq(a: f32, b: f32, tolerance: f32) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+/// Whether `a` and `b` differ by at most `tolerance` times the larger magnitude, or
+/// `tolerance` itself near zero. The same tolerance then suits coordinates near the origin
+/// and far from it.
+pub fn relative_eq(a: f32, b: f32, tolerance: f32) -> bool {
+    (a - b).abs() <= tolerance * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Whether at most `max_ulps` representable `f32` values lie between `a` and `b`.
+pub fn ulps_eq(a: f32, b: f32, max_ulps: u32) -> bool {
+    if a.is_nan() || b.is_nan() {
+        return false;
+    }
+
+    // map the bit patterns onto a monotonic integer line, with -0.0 and 0.0 together
+    let ordered = |v: f32| {
+        let bits = v.to_bits() as i32;
+        if bits < 0 {
+            i32::MIN - bits
+        } else {
+            bits
+        }
+    };
+
+    (ordered(a) as i64 - ordered(b) as i64).unsigned_abs() <= max_ulps as u64
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn p(x: f32, y: f32) -> CanvasPoint {
+        CanvasPoint::new(x, y)
+    }
+
+    #[test]
+    fn orientation() {
+        assert!(orient2d(p(0.0, 0.0), p(1.0, 0.0), p(0.0, 1.0)) > 0.0);
+        assert!(orient2d(p(0.0, 0.0), p(0.0, 1.0), p(1.0, 0.0)) < 0.0);
+        assert_eq!(orient2d(p(0.0, 0.0), p(1.0, 1.0), p(3.0, 3.0)), 0.0);
+
+        // far from the origin, where f32 products round away the answer
+        let (a, b, c) = (p(0.0, 0.0), p(8193.0, 8191.0), p(8192.0, 8190.0));
+        assert_eq!((b - a).cross(c - a), 0.0);
+        assert!(orient2d(a, b, c) < 0.0);
+
+        let far = 3.0e7;
+        assert_eq!(
+            orient2d(
+                p(far, far),
+                p(far + 2.0, far + 2.0),
+                p(far + 8.0, far + 8.0)
+            ),
+            0.0
+        );
+        assert!(
+            orient2d(
+                p(far, far),
+                p(far + 2.0, far + 2.0),
+                p(far + 8.0, far + 10.0)
+            ) > 0.0
+        );
+    }
+
+    #[test]
+    fn exact_fallback_agrees() {
+        let cases = [
+            (p(0.5, 0.5), p(12.0, 12.0), p(24.0, 24.0)),
+            (p(1e30, 1e-30), p(-1e30, 3.0), p(1e-30, 1e30)),
+            (p(3.0e7, -2.0), p(-3.0e7, 2.0), p(1.0, 1e-7)),
+            (p(0.1, 0.2), p(0.3, 0.4), p(0.5, 0.6000001)),
+        ];
+
+        for (a, b, c) in cases {
+            assert_eq!(orient2d(a, b, c).signum(), orient2d_exact(a, b, c).signum());
+        }
+
+        // b sits just below the diagonal through a and c, but 3e18 + 1 rounds to 3e18 in
+        // f64, so only the exact stage sees it
+        let (a, b, c) = (p(3e18, 3e18), p(1.0, 0.0), p(-3e18, -3e18));
+        assert!(orient2d(a, b, c) < 0.0);
+        assert!(orient2d(c, b, a) > 0.0);
+    }
+
+    #[test]
+    fn in_circle() {
+        let (a, b, c) = (p(0.0, -1.0), p(1.0, 0.0), p(0.0, 1.0));
+
+        assert!(incircle(a, b, c, p(0.0, 0.0)) > 0.0);
+        assert!(incircle(a, b, c, p(2.0, 0.0)) < 0.0);
+        assert_eq!(incircle(a, b, c, p(-1.0, 0.0)), 0.0);
+
+        let far = 1.0e6;
+        let (a, b, c) = (p(far, far - 4.0), p(far + 4.0, far), p(far, far + 4.0));
+        assert_eq!(incircle(a, b, c, p(far - 4.0, far)), 0.0);
+        assert!(incircle(a, b, c, p(far - 3.875, far)) > 0.0);
+        assert_eq!(
+            incircle(a, b, c, p(far - 4.125, far)).signum(),
+            incircle_exact(a, b, c, p(far - 4.125, far)).signum()
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(approx_eq(1.0, 1.05, 0.1));
+        assert!(!approx_eq(1.0e7, 1.0e7 + 2.0, 1e-3));
+
+        assert!(relative_eq(1.0e7, 1.0e7 + 2.0, 1e-6));
+        assert!(!relative_eq(1.0e7, 1.0e7 + 20.0, 1e-6));
+        assert!(relative_eq(1e-9, -1e-9, 1e-6));
+
+        assert!(ulps_eq(1.0, 1.0 + f32::EPSILON, 1));
+        assert!(!ulps_eq(1.0, 1.0 + 2.0 * f32::EPSILON, 1));
+        assert!(ulps_eq(-0.0, 0.0, 0));
+        assert!(ulps_eq(
+            -f32::MIN_POSITIVE,
+            f32::MIN_POSITIVE,
+            2 * (1 << 23)
+        ));
+        assert!(!ulps_eq(f32::NAN, f32::NAN, u32::MAX));
+    }
+}
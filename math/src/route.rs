@@ -0,0 +1,383 @@
+//! Orthogonal connector routing around axis-aligned obstacles.
+//!
+//! Routes run along a sparse grid through the sides of every obstacle, widened by a
+//! margin, and the points where the route leaves and enters its ends. A shortest path on
+//! that grid, with a cost for every bend, gives a tidy elbow connector.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::{Aabb, CanvasPoint};
+
+/// One end of a route: the point it starts or ends at, and the box around that point, if
+/// any. The route leaves or enters the box at right angles through the side nearest the
+/// point.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Endpoint {
+    pub point: CanvasPoint,
+    pub around: Option<Aabb>,
+}
+
+impl Endpoint {
+    pub fn free(point: CanvasPoint) -> Self {
+        Self {
+            point,
+            around: None,
+        }
+    }
+
+    pub fn on(point: CanvasPoint, around: Aabb) -> Self {
+        Self {
+            point,
+            around: Some(around),
+        }
+    }
+}
+
+/// The unit steps along the grid: right, left, down and up.
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+fn opposite(direction: usize) -> usize {
+    direction ^ 1
+}
+
+/// Where the route crosses the side of `end`'s box nearest its point, where it is `margin`
+/// clear of that side, and the direction pointing out of the box. When sides tie, as they
+/// do for a point in the middle, the one facing `toward` wins. Ends without a box leave
+/// from their point in any direction.
+fn leave(
+    end: Endpoint,
+    toward: CanvasPoint,
+    margin: f32,
+) -> (CanvasPoint, CanvasPoint, Option<usize>) {
+    let Some(around) = end.around else {
+        return (end.point, end.point, None);
+    };
+
+    let p = end.point.clamp(around.min, around.max);
+    let (x, y) = p.coord();
+    let (min_x, min_y) = around.min.coord();
+    let (max_x, max_y) = around.max.coord();
+
+    let sides = [max_x - x, x - min_x, max_y - y, y - min_y];
+    let facing = |d: usize| {
+        let (dx, dy) = DIRECTIONS[d];
+        (toward - p).dot(CanvasPoint::new(dx as f32, dy as f32))
+    };
+    let direction = (0..4)
+        .min_by(|&a, &b| {
+            sides[a]
+                .total_cmp(&sides[b])
+                .then(facing(b).total_cmp(&facing(a)))
+        })
+        .expect("a box has four sides");
+    let border = match direction {
+        0 => CanvasPoint::new(max_x, y),
+        1 => CanvasPoint::new(min_x, y),
+        2 => CanvasPoint::new(x, max_y),
+        _ => CanvasPoint::new(x, min_y),
+    };
+    let (dx, dy) = DIRECTIONS[direction];
+
+    (
+        border,
+        border + CanvasPoint::new(dx as f32, dy as f32) * CanvasPoint::splat(margin),
+        Some(direction),
+    )
+}
+
+/// Sorted coordinates with near-duplicates, within `epsilon`, merged.
+fn grid_lines(mut values: Vec<f32>, epsilon: f32) -> Vec<f32> {
+    values.sort_by(f32::total_cmp);
+    values.dedup_by(|a, b| (*a - *b).abs() <= epsilon);
+    values
+}
+
+fn nearest(lines: &[f32], value: f32) -> usize {
+    (0..lines.len())
+        .min_by(|&a, &b| {
+            (lines[a] - value)
+                .abs()
+                .total_cmp(&(lines[b] - value).abs())
+        })
+        .expect("the grid has a line through every end")
+}
+
+/// Drops points within `epsilon` of the one before and points in the middle of straight
+/// runs.
+fn simplify(points: Vec<CanvasPoint>, epsilon: f32) -> Vec<CanvasPoint> {
+    let mut path: Vec<CanvasPoint> = Vec::with_capacity(points.len());
+
+    for p in points {
+        if path
+            .last()
+            .is_some_and(|&last| last.euclid_dist(p) <= epsilon)
+        {
+            continue;
+        }
+        if let [.., a, b] = path[..] {
+            if (b - a).cross(p - b).abs() <= epsilon * (p - a).length() {
+                path.pop();
+            }
+        }
+        path.push(p);
+    }
+
+    path
+}
+
+#[derive(PartialEq)]
+struct Visit {
+    cost: f32,
+    state: usize,
+}
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    /// Reversed, so the heap pops the cheapest visit first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A path of horizontal and vertical segments from `start` to `end` that keeps `margin`
+/// clear of every box in `obstacles` and of the ends' own boxes, preferring fewer bends.
+/// `margin` must be positive.
+///
+/// When there is no such path, for example because an end is buried in an obstacle, the
+/// route falls back to a single elbow that ignores the obstacles.
+pub fn orthogonal(
+    start: Endpoint,
+    end: Endpoint,
+    obstacles: &[Aabb],
+    margin: f32,
+) -> Vec<CanvasPoint> {
+    let (start_border, start_exit, start_direction) = leave(start, end.point, margin);
+    let (end_border, end_exit, end_direction) = leave(end, start.point, margin);
+    let epsilon = margin * 1e-3;
+    let bend = margin * 4.0;
+
+    let blocked: Vec<Aabb> = obstacles
+        .iter()
+        .chain(start.around.iter())
+        .chain(end.around.iter())
+        .map(|b| b.expand(margin))
+        .collect();
+    // strictly inside, so routes may run along the widened sides
+    let is_blocked = |p: CanvasPoint| blocked.iter().any(|b| b.expand(-epsilon).contains(p));
+
+    let midpoint = start_exit.midpoint(end_exit);
+    let (xs, ys): (Vec<f32>, Vec<f32>) = blocked
+        .iter()
+        .flat_map(|b| [b.min.coord(), b.max.coord()])
+        .chain([start_exit.coord(), end_exit.coord(), midpoint.coord()])
+        .unzip();
+    let (xs, ys) = (grid_lines(xs, epsilon), grid_lines(ys, epsilon));
+    let (nx, ny) = (xs.len() as i32, ys.len() as i32);
+    let point = |i: i32, j: i32| CanvasPoint::new(xs[i as usize], ys[j as usize]);
+
+    // a state is a node and the direction the route arrived in, or 4 at the start
+    let state = |i: i32, j: i32, direction: usize| ((j * nx + i) as usize) * 5 + direction;
+    let from = (
+        nearest(&xs, start_exit.one()) as i32,
+        nearest(&ys, start_exit.two()) as i32,
+    );
+    let to = (
+        nearest(&xs, end_exit.one()) as i32,
+        nearest(&ys, end_exit.two()) as i32,
+    );
+
+    let mut cost = vec![f32::INFINITY; (nx * ny) as usize * 5];
+    let mut previous = vec![usize::MAX; cost.len()];
+    let mut queue = BinaryHeap::new();
+
+    let first = state(from.0, from.1, start_direction.unwrap_or(4));
+    cost[first] = 0.0;
+    queue.push(Visit {
+        cost: 0.0,
+        state: first,
+    });
+
+    let mut goal = None;
+    while let Some(Visit { cost: c, state: s }) = queue.pop() {
+        if c > cost[s] {
+            continue;
+        }
+
+        let direction = s % 5;
+        let node = (s / 5) as i32;
+        let (i, j) = (node % nx, node / nx);
+
+        if (i, j) == to {
+            goal = Some(s);
+            break;
+        }
+
+        for (d, (dx, dy)) in DIRECTIONS.into_iter().enumerate() {
+            if direction < 4 && d == opposite(direction) {
+                continue;
+            }
+
+            let (ni, nj) = (i + dx, j + dy);
+            if ni < 0 || nj < 0 || ni >= nx || nj >= ny {
+                continue;
+            }
+
+            let (a, b) = (point(i, j), point(ni, nj));
+            if is_blocked(b) || is_blocked(a.midpoint(b)) {
+                continue;
+            }
+
+            let mut next_cost = c + a.euclid_dist(b);
+            if direction < 4 && d != direction {
+                next_cost += bend;
+            }
+            // arriving at the end other than straight into its box costs a bend more
+            if (ni, nj) == to && end_direction.is_some_and(|e| d != opposite(e)) {
+                next_cost += bend;
+            }
+
+            let next = state(ni, nj, d);
+            if next_cost < cost[next] {
+                cost[next] = next_cost;
+                previous[next] = s;
+                queue.push(Visit {
+                    cost: next_cost,
+                    state: next,
+                });
+            }
+        }
+    }
+
+    let middle = match goal {
+        Some(mut s) => {
+            let mut nodes = Vec::new();
+            loop {
+                let node = (s / 5) as i32;
+                nodes.push(point(node % nx, node / nx));
+
+                if s == first {
+                    break;
+                }
+                s = previous[s];
+            }
+            nodes.reverse();
+            nodes
+        }
+        None => vec![
+            start_exit,
+            CanvasPoint::new(end_exit.one(), start_exit.two()),
+        ],
+    };
+
+    simplify(
+        [start_border, start_exit]
+            .into_iter()
+            .chain(middle)
+            .chain([end_exit, end_border])
+            .collect(),
+        epsilon,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::polyline;
+
+    fn p(x: f32, y: f32) -> CanvasPoint {
+        CanvasPoint::new(x, y)
+    }
+
+    fn is_orthogonal(path: &[CanvasPoint]) -> bool {
+        polyline::segments(path).all(|s| {
+            let (dx, dy) = s.direction().coord();
+            dx == 0.0 || dy == 0.0
+        })
+    }
+
+    fn misses(path: &[CanvasPoint], obstacle: Aabb) -> bool {
+        !polyline::intersects_aabb(path, obstacle.expand(-0.01))
+    }
+
+    #[test]
+    fn straight_when_clear() {
+        let a = Aabb::new(p(0.0, 0.0), p(20.0, 20.0));
+        let b = Aabb::new(p(100.0, 0.0), p(120.0, 20.0));
+
+        // from the right side of one box straight into the left side of the other
+        let path = orthogonal(
+            Endpoint::on(p(18.0, 10.0), a),
+            Endpoint::on(p(102.0, 10.0), b),
+            &[],
+            5.0,
+        );
+        assert_eq!(path, vec![p(20.0, 10.0), p(100.0, 10.0)]);
+    }
+
+    #[test]
+    fn around_an_obstacle() {
+        let a = Aabb::new(p(0.0, 0.0), p(20.0, 20.0));
+        let b = Aabb::new(p(100.0, 0.0), p(120.0, 20.0));
+        let wall = Aabb::new(p(50.0, -40.0), p(60.0, 50.0));
+
+        let path = orthogonal(
+            Endpoint::on(p(18.0, 10.0), a),
+            Endpoint::on(p(102.0, 10.0), b),
+            &[wall],
+            5.0,
+        );
+
+        assert!(is_orthogonal(&path));
+        assert!(misses(&path, wall.expand(4.9)));
+        assert_eq!(path.first(), Some(&p(20.0, 10.0)));
+        assert_eq!(path.last(), Some(&p(100.0, 10.0)));
+        // under the wall, which is the shorter way round
+        assert!(path.iter().any(|q| q.two() == 55.0));
+        assert_eq!(path.len(), 6);
+    }
+
+    #[test]
+    fn elbow_between_offset_boxes() {
+        let a = Aabb::new(p(0.0, 0.0), p(20.0, 20.0));
+        let b = Aabb::new(p(100.0, 100.0), p(120.0, 120.0));
+
+        // out of the bottom of one and into the top of the other
+        let path = orthogonal(
+            Endpoint::on(p(10.0, 18.0), a),
+            Endpoint::on(p(110.0, 102.0), b),
+            &[],
+            5.0,
+        );
+
+        assert!(is_orthogonal(&path));
+        assert!(misses(&path, a) && misses(&path, b));
+        assert_eq!(path.first(), Some(&p(10.0, 20.0)));
+        assert_eq!(path.last(), Some(&p(110.0, 100.0)));
+        assert_eq!(path.len(), 4);
+    }
+
+    #[test]
+    fn falls_back_when_buried() {
+        let wall = Aabb::new(p(-100.0, -100.0), p(100.0, 100.0));
+
+        let path = orthogonal(
+            Endpoint::free(p(0.0, 0.0)),
+            Endpoint::free(p(200.0, 50.0)),
+            &[wall],
+            5.0,
+        );
+
+        assert_eq!(path, vec![p(0.0, 0.0), p(200.0, 0.0), p(200.0, 50.0)]);
+    }
+}
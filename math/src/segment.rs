@@ -0,0 +1,212 @@
+use crate::predicates::orient2d;
+use crate::{Aabb, CanvasPoint};
+
+/// A straight line segment between two points.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Segment {
+    pub from: CanvasPoint,
+    pub to: CanvasPoint,
+}
+
+impl Segment {
+    pub fn new(from: CanvasPoint, to: CanvasPoint) -> Self {
+        Self { from, to }
+    }
+
+    pub fn direction(&self) -> CanvasPoint {
+        self.to - self.from
+    }
+
+    pub fn length(&self) -> f32 {
+        self.from.euclid_dist(self.to)
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_corners(self.from, self.to)
+    }
+
+    /// The parameter `t` in `[0, 1]` of the point on the segment nearest to `point`.
+    pub fn project(&self, point: CanvasPoint) -> f32 {
+        let d = self.direction();
+        let len_sq = d.dot(d);
+
+        if len_sq == 0.0 {
+            return 0.0;
+        }
+
+        ((point - self.from).dot(d) / len_sq).clamp(0.0, 1.0)
+    }
+
+    pub fn closest_point(&self, point: CanvasPoint) -> CanvasPoint {
+        self.from.lerp(self.to, self.project(point))
+    }
+
+    pub fn distance(&self, point: CanvasPoint) -> f32 {
+        self.closest_point(point).euclid_dist(point)
+    }
+
+    /// Which side of the segment's line `point` falls on: 1 to the left (y-up), -1 to the
+    /// right and 0 on it, decided exactly.
+    pub fn side(&self, point: CanvasPoint) -> i32 {
+        let orientation = orient2d(self.from, self.to, point);
+
+        (orientation > 0.0) as i32 - (orientation < 0.0) as i32
+    }
+
+    /// Returns the point where the two segments meet. Overlapping collinear segments
+    /// report the first shared point along `self`.
+    ///
+    /// Whether they meet is decided with exact predicates, so it never contradicts
+    /// [`Segment::side`]; only the reported point is rounded.
+    pub fn intersection(&self, other: Segment) -> Option<CanvasPoint> {
+        let (from_side, to_side) = (other.side(self.from), other.side(self.to));
+        let (other_from_side, other_to_side) = (self.side(other.from), self.side(other.to));
+
+        if from_side * to_side > 0 || other_from_side * other_to_side > 0 {
+            // both ends of one segment lie strictly on the same side of the other
+            return None;
+        }
+
+        let r = self.direction();
+        let s = other.direction();
+        let qp = other.from - self.from;
+
+        if [from_side, to_side, other_from_side, other_to_side] == [0; 4] {
+            let len_sq = r.dot(r);
+            if len_sq == 0.0 {
+                return (other.distance(self.from) == 0.0).then_some(self.from);
+            }
+
+            // collinear, so compare the extents of `other` along `self`
+            let t0 = qp.dot(r) / len_sq;
+            let t1 = t0 + s.dot(r) / len_sq;
+            let (lo, hi) = (t0.min(t1).max(0.0), t0.max(t1).min(1.0));
+
+            return (lo <= hi).then(|| self.from.lerp(self.to, lo));
+        }
+
+        let denom = r.cross(s);
+        let t = match denom {
+            // too close to parallel for f32, so fall back on the touching end
+            0.0 if to_side == 0 => 1.0,
+            0.0 => 0.0,
+            denom => (qp.cross(s) / denom).clamp(0.0, 1.0),
+        };
+
+        Some(self.from.lerp(self.to, t))
+    }
+
+    pub fn intersects(&self, other: Segment) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Clips the segment to the part inside `aabb` with the Liang–Barsky slab method.
+    pub fn clip(&self, aabb: Aabb) -> Option<Segment> {
+        let d = self.direction();
+
+        // entry and exit parameters of both slabs at once
+        let ta = (aabb.min - self.from) / d;
+        let tb = (aabb.max - self.from) / d;
+        let (near, far) = (ta.min(tb), ta.max(tb));
+
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+        let lanes: [fn(CanvasPoint) -> f32; 2] = [CanvasPoint::one, CanvasPoint::two];
+
+        for lane in lanes {
+            if lane(d) == 0.0 {
+                // parallel to this slab, so it is either always inside it or never
+                let from = lane(self.from);
+                if from < lane(aabb.min) || from > lane(aabb.max) {
+                    return None;
+                }
+                continue;
+            }
+
+            t0 = t0.max(lane(near));
+            t1 = t1.min(lane(far));
+        }
+
+        (t0 <= t1).then(|| Segment::new(self.from.lerp(self.to, t0), self.from.lerp(self.to, t1)))
+    }
+
+    pub fn intersects_aabb(&self, aabb: Aabb) -> bool {
+        self.clip(aabb).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    fn segment(x1: f32, y1: f32, x2: f32, y2: f32) -> Segment {
+        Segment::new(CanvasPoint::new(x1, y1), CanvasPoint::new(x2, y2))
+    }
+
+    #[test]
+    fn distance() {
+        let s = segment(0.0, 0.0, 10.0, 0.0);
+
+        assert_eq!(s.distance(CanvasPoint::new(5.0, 3.0)), 3.0);
+        assert_eq!(s.distance(CanvasPoint::new(-3.0, 4.0)), 5.0);
+        assert_eq!(s.distance(CanvasPoint::new(13.0, -4.0)), 5.0);
+        assert_eq!(
+            segment(1.0, 1.0, 1.0, 1.0).distance(CanvasPoint::new(4.0, 5.0)),
+            5.0
+        );
+    }
+
+    #[test]
+    fn intersection() {
+        let s = segment(0.0, 0.0, 10.0, 10.0);
+
+        assert_eq!(
+            s.intersection(segment(0.0, 10.0, 10.0, 0.0)),
+            Some(CanvasPoint::new(5.0, 5.0))
+        );
+        assert_eq!(s.intersection(segment(0.0, 10.0, 4.0, 6.0)), None);
+        assert_eq!(s.intersection(segment(0.0, 1.0, 10.0, 11.0)), None);
+        assert_eq!(
+            s.intersection(segment(20.0, 20.0, 5.0, 5.0)),
+            Some(CanvasPoint::new(5.0, 5.0))
+        );
+        assert_eq!(s.intersection(segment(11.0, 11.0, 20.0, 20.0)), None);
+        assert!(s.intersects(segment(10.0, 10.0, 20.0, 0.0)));
+    }
+
+    #[test]
+    fn clip() {
+        let aabb = Aabb::new(CanvasPoint::new(0.0, 0.0), CanvasPoint::new(10.0, 10.0));
+
+        assert_eq!(
+            segment(-5.0, 5.0, 15.0, 5.0).clip(aabb),
+            Some(segment(0.0, 5.0, 10.0, 5.0))
+        );
+        assert_eq!(
+            segment(-5.0, -5.0, 5.0, 5.0).clip(aabb),
+            Some(segment(0.0, 0.0, 5.0, 5.0))
+        );
+        assert_eq!(
+            segment(2.0, 2.0, 3.0, 3.0).clip(aabb),
+            Some(segment(2.0, 2.0, 3.0, 3.0))
+        );
+        assert_eq!(segment(-5.0, 11.0, 15.0, 11.0).clip(aabb), None);
+        assert_eq!(segment(-5.0, 8.0, 8.0, 21.0).clip(aabb), None);
+        assert!(segment(5.0, -5.0, 5.0, 15.0).intersects_aabb(aabb));
+    }
+
+    #[test]
+    fn far_from_origin() {
+        // nearly parallel segments around x = 3e7, where f32 spacing is 2
+        let s = segment(3.0e7, 0.0, 3.0e7 + 64.0, 64.0);
+        let crossing = segment(3.0e7 + 32.0, 34.0, 3.0e7 + 36.0, 30.0);
+        let beside = segment(3.0e7 + 32.0, 36.0, 3.0e7 + 36.0, 38.0);
+
+        assert!(s.intersects(crossing));
+        assert!(!s.intersects(beside));
+        assert_eq!(s.side(CanvasPoint::new(3.0e7 + 32.0, 34.0)), 1);
+        assert_eq!(s.side(CanvasPoint::new(3.0e7 + 32.0, 32.0)), 0);
+    }
+}
@@ -0,0 +1,19 @@
+//! Lane-wise `f32x4` intrinsics backing every type in this crate.
+//!
+//! On wasm32 these are the native simd128 instructions. Every other target gets a
+//! scalar emulation with the same names and semantics, so geometry code compiles
+//! and tests natively while producing bit-identical results.
+
+#[cfg(target_arch = "wasm32")]
+pub use std::arch::wasm::{
+    f32x4, f32x4_abs, f32x4_add, f32x4_div, f32x4_eq, f32x4_extract_lane, f32x4_ge, f32x4_gt,
+    f32x4_le, f32x4_lt, f32x4_mul, f32x4_neg, f32x4_pmax, f32x4_pmin, f32x4_splat, f32x4_sqrt,
+    f32x4_sub, i32x4_all_true, i32x4_extract_lane, i32x4_shuffle, v128, v128_and, v128_bitselect,
+    v128_not, v128_or,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use scalar::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod scalar;
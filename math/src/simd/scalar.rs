@@ -0,0 +1,211 @@
+//! Scalar stand-ins for the `std::arch::wasm` intrinsics used by this crate.
+//!
+//! Lanes are stored as raw bits so that comparison masks round-trip through
+//! `i32x4_extract_lane` exactly like they do on wasm32.
+
+#![allow(non_camel_case_types)]
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C, align(16))]
+pub struct v128([u32; 4]);
+
+impl v128 {
+    #[inline(always)]
+    fn lanes(self) -> [f32; 4] {
+        self.0.map(f32::from_bits)
+    }
+
+    #[inline(always)]
+    fn from_lanes(lanes: [f32; 4]) -> Self {
+        Self(lanes.map(f32::to_bits))
+    }
+
+    #[inline(always)]
+    fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        Self::from_lanes(self.lanes().map(f))
+    }
+
+    #[inline(always)]
+    fn zip(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        let (a, b) = (self.lanes(), other.lanes());
+        Self::from_lanes([f(a[0], b[0]), f(a[1], b[1]), f(a[2], b[2]), f(a[3], b[3])])
+    }
+
+    #[inline(always)]
+    fn mask(self, other: Self, f: impl Fn(f32, f32) -> bool) -> Self {
+        let (a, b) = (self.lanes(), other.lanes());
+        let lane = |i: usize| if f(a[i], b[i]) { u32::MAX } else { 0 };
+        Self([lane(0), lane(1), lane(2), lane(3)])
+    }
+}
+
+#[inline(always)]
+pub fn f32x4(a: f32, b: f32, c: f32, d: f32) -> v128 {
+    v128::from_lanes([a, b, c, d])
+}
+
+#[inline(always)]
+pub fn f32x4_splat(a: f32) -> v128 {
+    v128::from_lanes([a; 4])
+}
+
+#[inline(always)]
+pub fn f32x4_extract_lane<const N: usize>(a: v128) -> f32 {
+    a.lanes()[N]
+}
+
+/// Picks lanes from the concatenation of `a` and `b`, indices `0..4` from `a` and `4..8` from `b`.
+#[inline(always)]
+pub fn i32x4_shuffle<const I0: usize, const I1: usize, const I2: usize, const I3: usize>(
+    a: v128,
+    b: v128,
+) -> v128 {
+    let lane = |i: usize| if i < 4 { a.0[i] } else { b.0[i - 4] };
+    v128([lane(I0), lane(I1), lane(I2), lane(I3)])
+}
+
+#[inline(always)]
+pub fn i32x4_all_true(a: v128) -> bool {
+    a.0.iter().all(|&lane| lane != 0)
+}
+
+#[inline(always)]
+pub fn i32x4_extract_lane<const N: usize>(a: v128) -> i32 {
+    a.0[N] as i32
+}
+
+#[inline(always)]
+pub fn f32x4_add(a: v128, b: v128) -> v128 {
+    a.zip(b, |a, b| a + b)
+}
+
+#[inline(always)]
+pub fn f32x4_sub(a: v128, b: v128) -> v128 {
+    a.zip(b, |a, b| a - b)
+}
+
+#[inline(always)]
+pub fn f32x4_mul(a: v128, b: v128) -> v128 {
+    a.zip(b, |a, b| a * b)
+}
+
+#[inline(always)]
+pub fn f32x4_div(a: v128, b: v128) -> v128 {
+    a.zip(b, |a, b| a / b)
+}
+
+#[inline(always)]
+pub fn f32x4_abs(a: v128) -> v128 {
+    a.map(f32::abs)
+}
+
+#[inline(always)]
+pub fn f32x4_neg(a: v128) -> v128 {
+    a.map(|a| -a)
+}
+
+#[inline(always)]
+pub fn f32x4_sqrt(a: v128) -> v128 {
+    a.map(f32::sqrt)
+}
+
+/// Pseudo-minimum, `b < a ? b : a`, which differs from `f32::min` on NaN and signed zero.
+#[inline(always)]
+pub fn f32x4_pmin(a: v128, b: v128) -> v128 {
+    a.zip(b, |a, b| if b < a { b } else { a })
+}
+
+/// Pseudo-maximum, `a < b ? b : a`, which differs from `f32::max` on NaN and signed zero.
+#[inline(always)]
+pub fn f32x4_pmax(a: v128, b: v128) -> v128 {
+    a.zip(b, |a, b| if a < b { b } else { a })
+}
+
+#[inline(always)]
+pub fn f32x4_eq(a: v128, b: v128) -> v128 {
+    a.mask(b, |a, b| a == b)
+}
+
+#[inline(always)]
+pub fn f32x4_lt(a: v128, b: v128) -> v128 {
+    a.mask(b, |a, b| a < b)
+}
+
+#[inline(always)]
+pub fn f32x4_le(a: v128, b: v128) -> v128 {
+    a.mask(b, |a, b| a <= b)
+}
+
+#[inline(always)]
+pub fn f32x4_ge(a: v128, b: v128) -> v128 {
+    a.mask(b, |a, b| a >= b)
+}
+
+#[inline(always)]
+pub fn f32x4_gt(a: v128, b: v128) -> v128 {
+    a.mask(b, |a, b| a > b)
+}
+
+#[inline(always)]
+pub fn v128_and(a: v128, b: v128) -> v128 {
+    v128([0, 1, 2, 3].map(|i| a.0[i] & b.0[i]))
+}
+
+#[inline(always)]
+pub fn v128_or(a: v128, b: v128) -> v128 {
+    v128([0, 1, 2, 3].map(|i| a.0[i] | b.0[i]))
+}
+
+#[inline(always)]
+pub fn v128_not(a: v128) -> v128 {
+    v128(a.0.map(|lane| !lane))
+}
+
+/// Takes each bit from `a` where `mask` is set and from `b` where it is clear.
+#[inline(always)]
+pub fn v128_bitselect(a: v128, b: v128, mask: v128) -> v128 {
+    v128([0, 1, 2, 3].map(|i| (a.0[i] & mask.0[i]) | (b.0[i] & !mask.0[i])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comparison_masks() {
+        let res = f32x4_le(f32x4(1.0, 3.0, 0.0, 0.0), f32x4(2.0, 2.0, 0.0, 0.0));
+
+        assert_eq!(i32x4_extract_lane::<0>(res), -1);
+        assert_eq!(i32x4_extract_lane::<1>(res), 0);
+        assert_eq!(i32x4_extract_lane::<2>(res), -1);
+
+        let lt = f32x4_lt(f32x4(1.0, 2.0, f32::NAN, 0.0), f32x4(2.0, 2.0, 1.0, 0.0));
+        let eq = f32x4_eq(f32x4(1.0, 2.0, f32::NAN, 0.0), f32x4(2.0, 2.0, 1.0, -0.0));
+        assert_eq!(i32x4_extract_lane::<0>(v128_and(lt, eq)), 0);
+        assert_eq!(i32x4_extract_lane::<1>(v128_or(lt, eq)), -1);
+        assert_eq!(i32x4_extract_lane::<2>(v128_or(lt, eq)), 0);
+        assert_eq!(i32x4_extract_lane::<3>(eq), -1);
+        assert_eq!(i32x4_extract_lane::<3>(v128_not(eq)), 0);
+
+        let picked = v128_bitselect(f32x4_splat(1.0), f32x4_splat(2.0), lt);
+        assert_eq!(f32x4_extract_lane::<0>(picked), 1.0);
+        assert_eq!(f32x4_extract_lane::<1>(picked), 2.0);
+    }
+
+    #[test]
+    fn pseudo_min_max() {
+        let a = f32x4(f32::NAN, 1.0, -0.0, 0.0);
+        let b = f32x4(1.0, f32::NAN, 0.0, -0.0);
+
+        let min = f32x4_pmin(a, b);
+        assert!(f32x4_extract_lane::<0>(min).is_nan());
+        assert_eq!(f32x4_extract_lane::<1>(min), 1.0);
+        assert!(f32x4_extract_lane::<2>(min).is_sign_negative());
+        assert!(f32x4_extract_lane::<3>(min).is_sign_positive());
+
+        let max = f32x4_pmax(a, b);
+        assert!(f32x4_extract_lane::<0>(max).is_nan());
+        assert_eq!(f32x4_extract_lane::<1>(max), 1.0);
+        assert!(f32x4_extract_lane::<2>(max).is_sign_negative());
+    }
+}
@@ -0,0 +1,232 @@
+//! Polyline simplification. Both algorithms keep the first and last points.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::{CanvasPoint, Segment};
+
+/// Ramer–Douglas–Peucker: drops every point that lies within `tolerance` of the
+/// simplified polyline, so the result never strays further than `tolerance` from the input.
+pub fn ramer_douglas_peucker(points: &[CanvasPoint], tolerance: f32) -> Vec<CanvasPoint> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+
+    while let Some((first, last)) = stack.pop() {
+        let chord = Segment::new(points[first], points[last]);
+
+        let farthest = (first + 1..last)
+            .map(|i| (i, chord.distance(points[i])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((i, dist)) = farthest {
+            if dist > tolerance {
+                keep[i] = true;
+                stack.push((first, i));
+                stack.push((i, last));
+            }
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(&p, keep)| keep.then_some(p))
+        .collect()
+}
+
+/// A candidate for removal in Visvalingam–Whyatt, ordered so the heap pops the smallest area.
+#[derive(PartialEq)]
+struct Candidate {
+    area: f32,
+    index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .area
+            .total_cmp(&self.area)
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+fn triangle_area(a: CanvasPoint, b: CanvasPoint, c: CanvasPoint) -> f32 {
+    (b - a).cross(c - a).abs() / 2.0
+}
+
+/// Visvalingam–Whyatt: repeatedly removes the point whose triangle with its neighbours
+/// has the smallest area, until every remaining triangle is at least `min_area`.
+/// It tends to keep the overall shape of a stroke better than RDP at the same point count.
+pub fn visvalingam_whyatt(points: &[CanvasPoint], min_area: f32) -> Vec<CanvasPoint> {
+    let n = points.len();
+
+    if n < 3 {
+        return points.to_vec();
+    }
+
+    // a doubly linked list over the surviving indices
+    let mut prev: Vec<usize> = (0..n).map(|i| i.saturating_sub(1)).collect();
+    let mut next: Vec<usize> = (0..n).map(|i| (i + 1).min(n - 1)).collect();
+    let mut area: Vec<f32> = (0..n)
+        .map(|i| match i {
+            0 => f32::INFINITY,
+            i if i == n - 1 => f32::INFINITY,
+            i => triangle_area(points[i - 1], points[i], points[i + 1]),
+        })
+        .collect();
+    let mut removed = vec![false; n];
+
+    let mut heap: BinaryHeap<Candidate> = (1..n - 1)
+        .map(|index| Candidate {
+            area: area[index],
+            index,
+        })
+        .collect();
+
+    while let Some(Candidate {
+        area: smallest,
+        index,
+    }) = heap.pop()
+    {
+        if removed[index] || smallest != area[index] {
+            // stale entry, superseded after a neighbour was removed
+            continue;
+        }
+
+        if smallest >= min_area {
+            break;
+        }
+
+        removed[index] = true;
+        let (p, q) = (prev[index], next[index]);
+        next[p] = q;
+        prev[q] = p;
+
+        for neighbour in [p, q] {
+            if neighbour == 0 || neighbour == n - 1 {
+                continue;
+            }
+
+            let updated = triangle_area(
+                points[prev[neighbour]],
+                points[neighbour],
+                points[next[neighbour]],
+            )
+            // never let a neighbour become cheaper to remove than the point just removed
+            .max(smallest);
+
+            area[neighbour] = updated;
+            heap.push(Candidate {
+                area: updated,
+                index: neighbour,
+            });
+        }
+    }
+
+    points
+        .iter()
+        .zip(removed)
+        .filter_map(|(&p, removed)| (!removed).then_some(p))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::polyline;
+
+    fn noisy_line() -> Vec<CanvasPoint> {
+        (0..=100)
+            .map(|i| {
+                let jitter = if i % 2 == 0 { 0.1 } else { -0.1 };
+                CanvasPoint::new(i as f32, jitter)
+            })
+            .collect()
+    }
+
+    fn corner() -> Vec<CanvasPoint> {
+        (0..=10)
+            .map(|i| CanvasPoint::new(i as f32, 0.0))
+            .chain((1..=10).map(|i| CanvasPoint::new(10.0, i as f32)))
+            .collect()
+    }
+
+    #[test]
+    fn rdp_collapses_noise() {
+        let points = noisy_line();
+        let simplified = ramer_douglas_peucker(&points, 0.5);
+
+        assert_eq!(simplified, vec![points[0], points[100]]);
+    }
+
+    #[test]
+    fn rdp_keeps_corners() {
+        let simplified = ramer_douglas_peucker(&corner(), 0.5);
+
+        assert_eq!(
+            simplified,
+            vec![
+                CanvasPoint::new(0.0, 0.0),
+                CanvasPoint::new(10.0, 0.0),
+                CanvasPoint::new(10.0, 10.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn rdp_stays_within_tolerance() {
+        let points: Vec<CanvasPoint> = (0..200)
+            .map(|i| {
+                let t = i as f32 / 20.0;
+                CanvasPoint::new(t * 10.0, t.sin() * 20.0)
+            })
+            .collect();
+        let tolerance = 0.25;
+        let simplified = ramer_douglas_peucker(&points, tolerance);
+
+        assert!(simplified.len() < points.len() / 3);
+        assert!(points
+            .iter()
+            .all(|&p| polyline::distance(&simplified, p) <= tolerance));
+    }
+
+    #[test]
+    fn visvalingam() {
+        let points = noisy_line();
+        let simplified = visvalingam_whyatt(&points, 1.0);
+
+        assert!(simplified.len() < 25);
+        assert_eq!(simplified.first(), points.first());
+        assert_eq!(simplified.last(), points.last());
+
+        let simplified = visvalingam_whyatt(&corner(), 1.0);
+        assert_eq!(simplified.len(), 3);
+        assert_eq!(simplified[1], CanvasPoint::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn short_inputs_are_untouched() {
+        let points = vec![CanvasPoint::new(0.0, 0.0), CanvasPoint::new(1.0, 1.0)];
+
+        assert_eq!(ramer_douglas_peucker(&points, 10.0), points);
+        assert_eq!(visvalingam_whyatt(&points, 10.0), points);
+    }
+}
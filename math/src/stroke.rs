@@ -0,0 +1,349 @@
+//! Variable-width ink outlines for pen strokes, in the spirit of perfect-freehand.
+//!
+//! A stroke is a list of [`StrokePoint`]s as sampled from the pointer. [`outline`] turns
+//! it into a closed polygon around the centreline whose width follows the pen pressure
+//! and slows down to thin out when the pen moves fast, so the whole stroke can be
+//! drawn as a single filled path.
+
+use std::f32::consts::{FRAC_PI_8, PI};
+
+use crate::{CanvasPoint, Transform};
+
+/// What a mouse reports while a button is held, and the pressure at which a stroke is
+/// exactly [`StrokeOptions::size`] wide.
+pub const DEFAULT_PRESSURE: f32 = 0.5;
+
+/// One pointer sample: where it was, how hard the pen pressed, from 0 to 1, and when, in
+/// milliseconds.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StrokePoint {
+    pub point: CanvasPoint,
+    pub pressure: f32,
+    pub time: f64,
+}
+
+impl StrokePoint {
+    pub fn new(point: CanvasPoint, pressure: f32, time: f64) -> Self {
+        Self {
+            point,
+            pressure,
+            time,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StrokeOptions {
+    /// Width of the stroke at [`DEFAULT_PRESSURE`] while the pen is at rest.
+    pub size: f32,
+    /// How much pressure changes the width, from 0 for not at all to 1, where no
+    /// pressure draws nothing and full pressure doubles the width.
+    pub thinning: f32,
+    /// How much speed thins the stroke: at a speed of `1 / velocity_thinning` canvas
+    /// units per millisecond the stroke is half as wide.
+    pub velocity_thinning: f32,
+    /// How far each point lags behind the pointer, from 0 to 1, which irons out jitter.
+    pub streamline: f32,
+    /// How quickly pressure and speed follow the input, from 0 exclusive to 1, so a single
+    /// noisy sample cannot pinch the stroke.
+    pub smoothing: f32,
+}
+
+impl Default for StrokeOptions {
+    fn default() -> Self {
+        Self {
+            size: 4.0,
+            thinning: 0.6,
+            velocity_thinning: 0.5,
+            streamline: 0.5,
+            smoothing: 0.4,
+        }
+    }
+}
+
+/// The narrowest a stroke gets, as a fraction of `size`, so fast or light strokes stay
+/// visible.
+const MIN_WIDTH: f32 = 0.1;
+
+/// The angle between consecutive points on round caps and joins.
+const ARC_STEP: f32 = FRAC_PI_8;
+
+fn perpendicular(v: CanvasPoint) -> CanvasPoint {
+    let (x, y) = v.coord();
+    CanvasPoint::new(-y, x)
+}
+
+/// The points strictly between `from` and `from` turned by `angle` about `center`.
+fn arc(center: CanvasPoint, from: CanvasPoint, angle: f32) -> impl Iterator<Item = CanvasPoint> {
+    let steps = (angle.abs() / ARC_STEP).ceil().max(1.0) as usize;
+
+    (1..steps).map(move |i| {
+        center + Transform::rotate(angle * i as f32 / steps as f32).apply_vector(from)
+    })
+}
+
+/// Smooths positions by letting each point trail the input, and works out the half
+/// width at each from smoothed pressure and speed. Samples too close to the previous one
+/// to give a direction are dropped.
+fn centreline(points: &[StrokePoint], options: &StrokeOptions) -> Vec<(CanvasPoint, f32)> {
+    let Some(first) = points.first() else {
+        return Vec::new();
+    };
+
+    let follow = 0.15 + (1.0 - options.streamline.clamp(0.0, 1.0)) * 0.85;
+    let smoothing = options.smoothing.clamp(f32::EPSILON, 1.0);
+    let min_spacing = options.size * 0.05;
+
+    let radius = |pressure: f32, speed: f32| {
+        let width = 1.0 + options.thinning * (2.0 * pressure - 1.0);
+        let width = width / (1.0 + options.velocity_thinning * speed);
+
+        options.size * width.max(MIN_WIDTH) / 2.0
+    };
+
+    let mut pressure = first.pressure;
+    let mut speed = 0.0;
+    let mut previous = *first;
+    let mut centre = vec![(first.point, radius(pressure, speed))];
+
+    for (i, sample) in points.iter().enumerate().skip(1) {
+        let (last, _) = centre[centre.len() - 1];
+        // the final sample is kept where it was so the stroke ends under the pen
+        let point = if i == points.len() - 1 {
+            sample.point
+        } else {
+            last.lerp(sample.point, follow)
+        };
+
+        if point.euclid_dist(last) <= min_spacing {
+            continue;
+        }
+
+        let elapsed = (sample.time - previous.time) as f32;
+        if elapsed > 0.0 {
+            let current = sample.point.euclid_dist(previous.point) / elapsed;
+            speed += (current - speed) * smoothing;
+        }
+        pressure += (sample.pressure - pressure) * smoothing;
+        previous = *sample;
+
+        centre.push((point, radius(pressure, speed)));
+    }
+
+    centre
+}
+
+/// The outline of a stroke as a closed polygon, without repeating the first point. It
+/// may cross itself where the stroke does, so fill it with the nonzero rule.
+///
+/// Joins and both ends are round. A stroke that never moves is a dot.
+pub fn outline(points: &[StrokePoint], options: &StrokeOptions) -> Vec<CanvasPoint> {
+    let centre = centreline(points, options);
+
+    match centre.as_slice() {
+        [] => Vec::new(),
+        [(point, radius)] => {
+            let from = CanvasPoint::new(*radius, 0.0);
+            std::iter::once(*point + from)
+                .chain(arc(*point, from, 2.0 * PI))
+                .collect()
+        }
+        _ => {
+            let last = centre.len() - 1;
+            let direction = |i: usize| (centre[i + 1].0 - centre[i].0).normalize();
+
+            let mut left = Vec::with_capacity(centre.len());
+            let mut right = Vec::with_capacity(centre.len());
+
+            for (i, &(point, radius)) in centre.iter().enumerate() {
+                let r = CanvasPoint::splat(radius);
+
+                if i == 0 || i == last {
+                    let n = perpendicular(direction(i.min(last - 1)));
+                    left.push(point + n * r);
+                    right.push(point - n * r);
+                    continue;
+                }
+
+                let (d_in, d_out) = (direction(i - 1), direction(i));
+                let (n_in, n_out) = (perpendicular(d_in), perpendicular(d_out));
+                let turn = d_in.cross(d_out).atan2(d_in.dot(d_out));
+
+                if turn.abs() < ARC_STEP {
+                    let n = (n_in + n_out).normalize();
+                    left.push(point + n * r);
+                    right.push(point - n * r);
+                    continue;
+                }
+
+                // the outer side of the bend gets a round join, the inner side meets at
+                // the bisector, or at the centre when the stroke doubles back on itself
+                let bisector = n_in + n_out;
+                let inner = if bisector.length() > f32::EPSILON {
+                    bisector.normalize() * r
+                } else {
+                    CanvasPoint::splat(0.0)
+                };
+                let (outer, inner_side, sign) = if turn > 0.0 {
+                    (&mut right, &mut left, -1.0)
+                } else {
+                    (&mut left, &mut right, 1.0)
+                };
+                let from = n_in * r * CanvasPoint::splat(sign);
+
+                outer.push(point + from);
+                outer.extend(arc(point, from, turn));
+                outer.push(point + n_out * r * CanvasPoint::splat(sign));
+                inner_side.push(point - inner * CanvasPoint::splat(sign));
+            }
+
+            let (end, end_radius) = centre[last];
+            let (start, start_radius) = centre[0];
+            let end_normal = perpendicular(direction(last - 1)) * CanvasPoint::splat(end_radius);
+            let start_normal = perpendicular(direction(0)) * CanvasPoint::splat(start_radius);
+
+            let mut polygon = left;
+            polygon.extend(arc(end, end_normal, -PI));
+            polygon.extend(right.into_iter().rev());
+            polygon.extend(arc(start, -start_normal, -PI));
+            polygon
+        }
+    }
+}
+
+/// A closed outline as SVG path data, with quadratic curves through the midpoints of its
+/// edges so that corners between samples come out smooth.
+pub fn svg_path(outline: &[CanvasPoint]) -> String {
+    let Some(&first) = outline.first() else {
+        return String::new();
+    };
+
+    let start = first.midpoint(outline[1 % outline.len()]);
+    let (x, y) = start.coord();
+    let mut path = format!("M {x} {y}");
+
+    for i in 1..=outline.len() {
+        let control = outline[i % outline.len()];
+        let to = control.midpoint(outline[(i + 1) % outline.len()]);
+        let (cx, cy) = control.coord();
+        let (x, y) = to.coord();
+        path.push_str(&format!(" Q {cx} {cy} {x} {y}"));
+    }
+
+    path.push_str(" Z");
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+    use crate::polygon;
+
+    /// A straight stroke along the x axis, one sample per canvas unit every `ms`.
+    fn line(length: usize, pressure: impl Fn(usize) -> f32, ms: f64) -> Vec<StrokePoint> {
+        (0..=length)
+            .map(|i| StrokePoint::new(CanvasPoint::new(i as f32, 0.0), pressure(i), i as f64 * ms))
+            .collect()
+    }
+
+    /// Half the height of `outline` at `x`, measured from the axis.
+    fn half_width(outline: &[CanvasPoint], x: f32) -> f32 {
+        outline
+            .iter()
+            .filter(|p| (p.one() - x).abs() <= 0.5)
+            .map(|p| p.two().abs())
+            .fold(0.0, f32::max)
+    }
+
+    fn still() -> StrokeOptions {
+        StrokeOptions {
+            streamline: 0.0,
+            velocity_thinning: 0.0,
+            ..StrokeOptions::default()
+        }
+    }
+
+    #[test]
+    fn follows_pressure() {
+        let options = still();
+        let ramp = outline(&line(100, |i| i as f32 / 100.0, 16.0), &options);
+
+        assert!(half_width(&ramp, 10.0) < half_width(&ramp, 50.0));
+        assert!(half_width(&ramp, 50.0) < half_width(&ramp, 90.0));
+        assert!((half_width(&ramp, 50.0) - options.size / 2.0).abs() < 0.2);
+
+        // a mouse presses evenly, so the stroke is as wide as asked all along
+        let even = outline(&line(100, |_| DEFAULT_PRESSURE, 16.0), &options);
+        assert!((half_width(&even, 30.0) - 2.0).abs() < 1e-3);
+        assert!((half_width(&even, 70.0) - 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn thins_with_speed() {
+        let options = StrokeOptions {
+            velocity_thinning: 0.5,
+            ..still()
+        };
+
+        let slow = outline(&line(100, |_| DEFAULT_PRESSURE, 16.0), &options);
+        let fast = outline(&line(100, |_| DEFAULT_PRESSURE, 0.5), &options);
+        assert!(half_width(&fast, 60.0) < half_width(&slow, 60.0));
+        assert!(half_width(&fast, 60.0) >= options.size * MIN_WIDTH / 2.0);
+    }
+
+    #[test]
+    fn encloses_the_stroke() {
+        let samples: Vec<_> = [
+            (0.0, 0.0),
+            (40.0, 0.0),
+            (40.0, 40.0),
+            (0.0, 40.0),
+            (20.0, 60.0),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (x, y))| StrokePoint::new(CanvasPoint::new(x, y), 0.5, i as f64 * 100.0))
+        .collect();
+        let zigzag = outline(&samples, &still());
+
+        for inside in [
+            (0.0, 0.0),
+            (20.0, 1.5),
+            (40.0, 20.0),
+            (41.2, 41.2),
+            (20.0, 60.0),
+        ] {
+            let (x, y) = inside;
+            assert_ne!(polygon::winding_number(&zigzag, CanvasPoint::new(x, y)), 0);
+        }
+        for outside in [(20.0, 20.0), (20.0, 3.0), (-3.0, 0.0), (44.0, 44.0)] {
+            let (x, y) = outside;
+            assert_eq!(polygon::winding_number(&zigzag, CanvasPoint::new(x, y)), 0);
+        }
+    }
+
+    #[test]
+    fn dot_and_path() {
+        let dot = outline(
+            &[StrokePoint::new(CanvasPoint::new(5.0, 5.0), 0.5, 0.0)],
+            &still(),
+        );
+
+        assert_eq!(dot.len(), 16);
+        assert!(dot
+            .iter()
+            .all(|p| (p.euclid_dist(CanvasPoint::new(5.0, 5.0)) - 2.0).abs() < 1e-4));
+        assert!(outline(&[], &still()).is_empty());
+
+        let square =
+            [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)].map(|(x, y)| CanvasPoint::new(x, y));
+        assert_eq!(
+            svg_path(&square),
+            "M 1 0 Q 2 0 2 1 Q 2 2 1 2 Q 0 2 0 1 Q 0 0 1 0 Z"
+        );
+        assert_eq!(svg_path(&[]), "");
+    }
+}
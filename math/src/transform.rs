@@ -0,0 +1,252 @@
+use crate::CanvasPoint;
+
+/// A 2D affine transform, stored column-wise as the SVG matrix
+///
+/// ```text
+/// | a c e |
+/// | b d f |
+/// | 0 0 1 |
+/// ```
+///
+/// where `x_axis = (a, b)`, `y_axis = (c, d)` and `translation = (e, f)`. Applying it
+/// to a point is two lane-wise multiplies and two adds.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Transform {
+    pub x_axis: CanvasPoint,
+    pub y_axis: CanvasPoint,
+    pub translation: CanvasPoint,
+}
+
+impl Transform {
+    pub fn new(x_axis: CanvasPoint, y_axis: CanvasPoint, translation: CanvasPoint) -> Self {
+        Self {
+            x_axis,
+            y_axis,
+            translation,
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(
+            CanvasPoint::new(1.0, 0.0),
+            CanvasPoint::new(0.0, 1.0),
+            CanvasPoint::new(0.0, 0.0),
+        )
+    }
+
+    pub fn translate(offset: CanvasPoint) -> Self {
+        Self {
+            translation: offset,
+            ..Self::identity()
+        }
+    }
+
+    pub fn scale(factor: CanvasPoint) -> Self {
+        let (sx, sy) = factor.coord();
+
+        Self::new(
+            CanvasPoint::new(sx, 0.0),
+            CanvasPoint::new(0.0, sy),
+            CanvasPoint::new(0.0, 0.0),
+        )
+    }
+
+    /// Rotates counter-clockwise by `radians` in a y-up frame, which is clockwise on screen.
+    pub fn rotate(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+
+        Self::new(
+            CanvasPoint::new(cos, sin),
+            CanvasPoint::new(-sin, cos),
+            CanvasPoint::new(0.0, 0.0),
+        )
+    }
+
+    /// Shears both axes at once: x gains `tan(x_radians)` times y, and y gains
+    /// `tan(y_radians)` times x. With one angle zero this is SVG's `skewX` or `skewY`; with
+    /// both non-zero it lacks the `tan(x) · tan(y)` term of applying one after the other.
+    pub fn skew(x_radians: f32, y_radians: f32) -> Self {
+        Self::new(
+            CanvasPoint::new(1.0, y_radians.tan()),
+            CanvasPoint::new(x_radians.tan(), 1.0),
+            CanvasPoint::new(0.0, 0.0),
+        )
+    }
+
+    /// Returns `self ∘ other`, the transform that applies `other` first and then `self`.
+    pub fn compose(self, other: Transform) -> Self {
+        Self::new(
+            self.apply_vector(other.x_axis),
+            self.apply_vector(other.y_axis),
+            self.apply(other.translation),
+        )
+    }
+
+    /// Returns the transform that applies `self` first and then `next`.
+    pub fn then(self, next: Transform) -> Self {
+        next.compose(self)
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let (a, b) = self.x_axis.coord();
+        let (c, d) = self.y_axis.coord();
+
+        a * d - b * c
+    }
+
+    /// Returns `None` when the transform collapses the plane onto a line or a point.
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let (a, b) = self.x_axis.coord();
+        let (c, d) = self.y_axis.coord();
+        let inv_det = CanvasPoint::splat(1.0 / det);
+
+        let x_axis = CanvasPoint::new(d, -b) * inv_det;
+        let y_axis = CanvasPoint::new(-c, a) * inv_det;
+        let inverse = Self::new(x_axis, y_axis, CanvasPoint::new(0.0, 0.0));
+
+        Some(Self {
+            translation: -inverse.apply_vector(self.translation),
+            ..inverse
+        })
+    }
+
+    /// Applies the linear part only, ignoring translation. Use this for directions and sizes.
+    #[inline(always)]
+    pub fn apply_vector(&self, vector: CanvasPoint) -> CanvasPoint {
+        let (x, y) = vector.coord();
+
+        self.x_axis * CanvasPoint::splat(x) + self.y_axis * CanvasPoint::splat(y)
+    }
+
+    #[inline(always)]
+    pub fn apply(&self, point: CanvasPoint) -> CanvasPoint {
+        self.apply_vector(point) + self.translation
+    }
+
+    pub fn apply_in_place(&self, points: &mut [CanvasPoint]) {
+        points.iter_mut().for_each(|p| *p = self.apply(*p));
+    }
+
+    pub fn apply_all(&self, points: &[CanvasPoint]) -> Vec<CanvasPoint> {
+        points.iter().map(|&p| self.apply(p)).collect()
+    }
+
+    /// Formats the transform as an SVG `transform` attribute value.
+    pub fn svg(&self) -> String {
+        let (a, b) = self.x_axis.coord();
+        let (c, d) = self.y_axis.coord();
+        let (e, f) = self.translation.coord();
+
+        format!("matrix({a} {b} {c} {d} {e} {f})")
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    #[test]
+    fn translate_then_scale() {
+        let t = Transform::translate(CanvasPoint::new(1.0, 2.0))
+            .then(Transform::scale(CanvasPoint::new(2.0, 3.0)));
+
+        assert_eq!(
+            t.apply(CanvasPoint::new(1.0, 1.0)),
+            CanvasPoint::new(4.0, 9.0)
+        );
+    }
+
+    #[test]
+    fn rotate_quarter_turn() {
+        let t = Transform::rotate(FRAC_PI_2);
+
+        assert_eq!(
+            t.apply(CanvasPoint::new(1.0, 0.0)),
+            CanvasPoint::new(0.0, 1.0)
+        );
+        assert_eq!(
+            t.apply(CanvasPoint::new(0.0, 1.0)),
+            CanvasPoint::new(-1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn skew_x() {
+        let t = Transform::skew(std::f32::consts::FRAC_PI_4, 0.0);
+
+        assert_eq!(
+            t.apply(CanvasPoint::new(0.0, 2.0)),
+            CanvasPoint::new(2.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn skew_both_axes() {
+        let (x, y) = (0.3f32, 0.4f32);
+        let p = CanvasPoint::new(1.0, 1.0);
+
+        let q = Transform::skew(x, y).apply(p);
+        assert!(q.approx_eq(CanvasPoint::new(1.0 + x.tan(), 1.0 + y.tan()), 1e-6));
+
+        // one after the other also shears y by what x gained
+        let composed = Transform::skew(x, 0.0)
+            .then(Transform::skew(0.0, y))
+            .apply(p);
+        assert!((composed.two() - q.two() - x.tan() * y.tan()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn invert_roundtrip() {
+        let t = Transform::scale(CanvasPoint::new(2.0, 4.0))
+            .then(Transform::rotate(0.3))
+            .then(Transform::translate(CanvasPoint::new(5.0, -3.0)));
+        let inverse = t.invert().unwrap();
+        let p = CanvasPoint::new(3.0, 7.0);
+
+        assert_eq!(t.compose(inverse), Transform::identity());
+        assert!(inverse.apply(t.apply(p)).euclid_dist(p) < 1e-5);
+    }
+
+    #[test]
+    fn singular_has_no_inverse() {
+        assert!(Transform::scale(CanvasPoint::new(0.0, 1.0))
+            .invert()
+            .is_none());
+    }
+
+    #[test]
+    fn apply_in_place() {
+        let t = Transform::translate(CanvasPoint::new(1.0, 1.0));
+        let mut points = vec![CanvasPoint::new(0.0, 0.0), CanvasPoint::new(2.0, 3.0)];
+        t.apply_in_place(&mut points);
+
+        assert_eq!(
+            points,
+            vec![CanvasPoint::new(1.0, 1.0), CanvasPoint::new(3.0, 4.0)]
+        );
+    }
+
+    #[test]
+    fn svg() {
+        let t = Transform::translate(CanvasPoint::new(3.0, 4.0));
+
+        assert_eq!(t.svg(), "matrix(1 0 0 1 3 4)");
+    }
+}
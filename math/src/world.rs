@@ -0,0 +1,116 @@
+use std::ops::{Add, Neg, Sub};
+
+use crate::CanvasPoint;
+
+/// A position on the unbounded canvas, in double precision.
+///
+/// Geometry itself stays in `f32` [`CanvasPoint`]s for SIMD, expressed relative to a
+/// nearby `WorldPoint` such as a shape's anchor or the camera's origin. Only the
+/// difference between two world points is ever narrowed to `f32`, so a point is as exact
+/// `10⁷` units out as it is next to the origin.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct WorldPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl WorldPoint {
+    /// The side of the square chunks that anchors snap to. Local `f32` coordinates within
+    /// a chunk and its neighbours keep better than a thousandth of a unit of precision.
+    pub const CHUNK_SIZE: f64 = 4096.0;
+
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn coord(self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    /// The corner of the chunk holding this point, on the side of smaller coordinates.
+    pub fn chunk(self) -> Self {
+        let snap = |v: f64| (v / Self::CHUNK_SIZE).floor() * Self::CHUNK_SIZE;
+
+        Self::new(snap(self.x), snap(self.y))
+    }
+
+    /// This point relative to `origin`, narrowed to `f32` only after subtracting.
+    pub fn relative_to(self, origin: WorldPoint) -> CanvasPoint {
+        let (x, y) = (self - origin).coord();
+
+        CanvasPoint::new(x as f32, y as f32)
+    }
+
+    /// The point `offset` away from this one.
+    pub fn offset(self, offset: CanvasPoint) -> Self {
+        self + Self::from(offset)
+    }
+}
+
+impl From<CanvasPoint> for WorldPoint {
+    fn from(point: CanvasPoint) -> Self {
+        let (x, y) = point.coord();
+
+        Self::new(x as f64, y as f64)
+    }
+}
+
+impl Add for WorldPoint {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for WorldPoint {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Neg for WorldPoint {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    #[test]
+    fn chunks() {
+        let size = WorldPoint::CHUNK_SIZE;
+
+        assert_eq!(
+            WorldPoint::new(10.0, -10.0).chunk(),
+            WorldPoint::new(0.0, -size)
+        );
+        assert_eq!(
+            WorldPoint::new(3.0 * size, 2.5 * size).chunk(),
+            WorldPoint::new(3.0 * size, 2.0 * size)
+        );
+    }
+
+    #[test]
+    fn far_from_origin() {
+        // 1e7 + 0.25 has no f32 representation, but its offset from a nearby anchor does
+        let far = WorldPoint::new(1.0e7 + 0.25, -1.0e7 - 0.5);
+        assert_ne!((far.x as f32) as f64, far.x);
+
+        let anchor = far.chunk();
+        let local = far.relative_to(anchor);
+        assert_eq!(anchor.offset(local), far);
+
+        let nudged = anchor.offset(local + CanvasPoint::new(0.125, 0.0));
+        assert_eq!(nudged - far, WorldPoint::new(0.125, 0.0));
+    }
+}